@@ -1,18 +1,31 @@
 use std::ffi::OsString;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    job_log::LogStream, output_format::OutputFormat, retry_policy::RetryPolicy, task::Task,
+    NewJobOptions,
+};
 
 /// CLI program to run jobs with the Whisper job manager
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 pub struct Args {
-    /// Path within the storage folder on the server to run Whisper on, must be relative
-    pub filepath: String,
+    /// Path within the storage folder on the server to run Whisper on, must be relative. With --upload, path of a local file instead
+    #[arg(required = true)]
+    pub filepath: Option<String>,
+
+    /// Upload the local file at the given path to the server, instead of transcribing a file already on the server
+    #[arg(short, long)]
+    pub upload: bool,
 
     /// Endpoint to call
-    #[arg(short, long, default_value_t = String::from("http://127.0.0.1:8080"))]
+    #[arg(short, long, global = true, default_value_t = String::from("http://127.0.0.1:8080"))]
     pub endpoint: String,
 
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The directory to put the subtitle file in
     #[arg(short, long, default_value_t = String::from("output"))]
     pub output_dir: String,
@@ -28,4 +41,151 @@ pub struct Args {
     /// The amount of time to wait before timing out, in milliseconds, defaults to 1 min
     #[arg(short, long, default_value_t = 1000 * 60)]
     pub poll_interval: u64,
+
+    /// Poll the status of the job, instead of following its events as the server sends them
+    #[arg(long)]
+    pub no_events: bool,
+
+    /// The priority of the job, higher runs first when the server schedules jobs by priority
+    #[arg(long)]
+    pub priority: Option<i32>,
+
+    /// Who submits the job, used to share the server fairly between submitters if the server trusts clients, defaults to the current user
+    #[arg(long)]
+    pub submitter: Option<String>,
+
+    /// The maximum number of times the job runs when it fails, including the first attempt, defaults to the setting of the server
+    #[arg(long)]
+    pub max_attempts: Option<u32>,
+
+    /// How long the job can run on the server before it is killed, in seconds, defaults to the setting of the server
+    #[arg(long)]
+    pub max_runtime: Option<u64>,
+
+    /// How long the job can wait in the queue on the server before it expires, in seconds, defaults to the setting of the server
+    #[arg(long)]
+    pub max_queue_time: Option<u64>,
+
+    /// The language spoken in the file, or "auto" to detect it, defaults to the setting of the server
+    #[arg(short, long)]
+    pub language: Option<String>,
+
+    /// The whisper model to run with, defaults to the setting of the server
+    #[arg(short, long)]
+    pub model: Option<String>,
+
+    /// Whether to "transcribe" the speech or "translate" it to English, defaults to transcribing
+    #[arg(long)]
+    pub task: Option<Task>,
+
+    /// A format of the files to download, among srt, vtt, txt, tsv, json or all. Can be given more than once, defaults to srt
+    #[arg(short, long = "format")]
+    pub formats: Vec<OutputFormat>,
+
+    /// Extract and normalize the audio of the file with ffmpeg before transcribing it, defaults to the setting of the server
+    #[arg(long, conflicts_with = "no_preprocess")]
+    pub preprocess: bool,
+
+    /// Transcribe the file as is, even if the server preprocesses files by default
+    #[arg(long)]
+    pub no_preprocess: bool,
+
+    /// Only transcribe the file from this time on, in seconds. Timestamps still match the whole file
+    #[arg(long, conflicts_with = "no_preprocess")]
+    pub start: Option<f64>,
+
+    /// Only transcribe the file up to this time, in seconds
+    #[arg(long, conflicts_with = "no_preprocess")]
+    pub end: Option<f64>,
+
+    /// A label to find the job by when listing jobs on the server. Can be given more than once
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+
+    /// An http or https URL the server POSTs the outcome of the job to once it finishes
+    #[arg(long)]
+    pub callback_url: Option<String>,
+}
+
+impl Args {
+    /// The options of the job to run, as given on the command line.
+    pub fn job_options(&self) -> NewJobOptions {
+        NewJobOptions {
+            priority: self.priority,
+            submitter: self
+                .submitter
+                .clone()
+                .or_else(|| std::env::var("USER").ok()),
+            retry: self.max_attempts.map(|max_attempts| RetryPolicy {
+                max_attempts: Some(max_attempts),
+                ..Default::default()
+            }),
+            max_runtime_secs: self.max_runtime,
+            max_queue_time_secs: self.max_queue_time,
+            language: self.language.clone(),
+            model: self.model.clone(),
+            task: self.task,
+            output_formats: if self.formats.is_empty() {
+                None
+            } else {
+                Some(self.formats.clone())
+            },
+            preprocess: match (self.preprocess, self.no_preprocess) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            start_secs: self.start,
+            end_secs: self.end,
+            tags: if self.tags.is_empty() {
+                None
+            } else {
+                Some(self.tags.clone())
+            },
+            callback_url: self.callback_url.clone(),
+        }
+    }
+}
+
+/// Commands other than running a job
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print the logs of a job
+    Logs(LogsArgs),
+    /// Run a job for every given file, or every file of a directory, and download their files as a ZIP archive. Takes the options of the
+    /// jobs before the command, e.g. `--language en batch --glob "*.mkv"`
+    Batch(BatchArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BatchArgs {
+    /// Paths within the storage folder on the server of the files to transcribe
+    #[arg(conflicts_with_all = ["directory", "glob"], required_unless_present_any = ["directory", "glob"])]
+    pub paths: Vec<String>,
+
+    /// The directory within the storage folder on the server to look for files in, defaults to the storage folder
+    #[arg(short, long)]
+    pub directory: Option<String>,
+
+    /// The pattern of the files to transcribe in the directory, e.g. "*.mkv" or "**/*.mp4", defaults to every file of the directory
+    #[arg(short, long)]
+    pub glob: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LogsArgs {
+    /// The UUID of the job
+    pub uuid: Uuid,
+
+    /// The stream to print, among stdout, stderr or both
+    #[arg(short, long, default_value_t = LogStream::Both)]
+    pub stream: LogStream,
+
+    /// Only print the given number of lines at the end of the logs
+    #[arg(long)]
+    pub tail: Option<usize>,
+
+    /// Keep printing new lines while the job runs
+    #[arg(short, long)]
+    pub follow: bool,
 }
@@ -5,20 +5,31 @@ use std::{
 };
 
 use clap::Parser;
-use reqwest::Client;
-use tokio::io::AsyncWriteExt;
+use reqwest::{
+    multipart::{Form, Part},
+    Body, Client, Response,
+};
+use tokio::io::{AsyncWriteExt, Stdout};
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 use whisper_job_manager_models::{
-    job_metadata::JobMetadata, job_status::JobStatus, CancelJobRequest, GetStatusResponse,
-    NewJobRequest, NewJobResponse,
+    batch::{CancelBatchRequest, GetBatchResponse, NewBatchRequest, NewBatchResponse},
+    error::ErrorResponse,
+    job_event::{JobEvent, JobEventKind},
+    job_log::LogStream,
+    job_metadata::JobMetadata,
+    job_status::JobStatus,
+    output_format::OutputFormat,
+    CancelJobRequest, GetJobLogsResponse, GetStatusResponse, NewJobOptions, NewJobRequest,
+    NewJobResponse,
 };
 
-use crate::args::Args;
+use crate::args::{Args, BatchArgs, Command, LogsArgs};
 
 pub mod args;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
     std::env::set_var("RUST_LOG", "debug");
     env_logger::init();
 
@@ -26,7 +37,70 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     log::info!("Running CLI with the following arguments: {args:?}");
 
-    run_job(args).await
+    let result = match &args.command {
+        Some(Command::Logs(logs_args)) => print_logs(&args.endpoint, logs_args).await,
+        Some(Command::Batch(batch_args)) => run_batch(&args, batch_args).await,
+        None => run_job(args).await,
+    };
+
+    if let Err(e) = result {
+        log::error!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Pass on a successful response, or turn a failed one into the error the server sent with it.
+async fn check_response(resp: Response) -> Result<Response, Box<dyn std::error::Error>> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+
+    let url = resp.url().path().to_string();
+    let body = resp.bytes().await?;
+
+    match serde_json::from_slice::<ErrorResponse>(&body) {
+        Ok(e) => Err(format!("{} failed with {}: {}", url, status, e).into()),
+        // Servers from before error bodies respond with an empty one
+        Err(_) => Err(format!("{} failed with {}", url, status).into()),
+    }
+}
+
+/// Upload the local file at the given path to the server, along with the options of the job to run on it.
+async fn upload_job(
+    client: &Client,
+    endpoint: &str,
+    path: &str,
+    options: NewJobOptions,
+) -> Result<NewJobResponse, Box<dyn std::error::Error>> {
+    let file = tokio::fs::File::open(path).await?;
+    let size = file.metadata().await?.len();
+    let filename = PathBuf::from(path)
+        .file_name()
+        .ok_or_else(|| format!("{} is not a file", path))?
+        .to_string_lossy()
+        .into_owned();
+    let content_type = mime_guess::from_path(path).first_or_octet_stream();
+
+    log::info!("Uploading {} ({} bytes, {})", path, size, content_type);
+
+    let body = Body::wrap_stream(ReaderStream::new(file));
+    let form = Form::new()
+        .text("options", serde_json::to_string(&options)?)
+        .part(
+            "file",
+            Part::stream_with_length(body, size)
+                .file_name(filename)
+                .mime_str(content_type.essence_str())?,
+        );
+
+    let resp = client
+        .post(format!("{}/uploadJob", endpoint))
+        .multipart(form)
+        .send()
+        .await?;
+
+    Ok(check_response(resp).await?.json::<NewJobResponse>().await?)
 }
 
 async fn run_job(args: Args) -> Result<(), Box<dyn std::error::Error>> {
@@ -35,31 +109,49 @@ async fn run_job(args: Args) -> Result<(), Box<dyn std::error::Error>> {
     // Create output directory
     tokio::fs::create_dir_all(&args.output_dir).await?;
 
-    let new_job_resp = client
-        .post(format!("{}/newJob", &args.endpoint))
-        .json(&NewJobRequest {
-            path: args.filepath.clone(),
-        })
-        .send()
-        .await?
-        .json::<NewJobResponse>()
-        .await?;
+    // The file path is required when there is no command
+    let filepath = args.filepath.clone().unwrap_or_default();
+    let new_job_resp = if args.upload {
+        upload_job(&client, &args.endpoint, &filepath, args.job_options()).await?
+    } else {
+        let resp = client
+            .post(format!("{}/newJob", &args.endpoint))
+            .json(&NewJobRequest {
+                path: filepath,
+                options: args.job_options(),
+            })
+            .send()
+            .await?;
+        check_response(resp).await?.json::<NewJobResponse>().await?
+    };
 
-    log::info!("Received response from /newJob: {new_job_resp:?}");
+    log::info!("Received response from the server: {new_job_resp:?}");
 
     let uuid = new_job_resp.uuid;
 
     let poll_interval = Duration::from_millis(args.poll_interval);
     let start = Instant::now();
-    let mut filename = match &args.name {
-        Some(s) => s.clone(),
-        None => OsString::new(),
-    };
+    let timeout = Duration::from_millis(args.timeout);
+
+    // Wait for the job to finish by following its events, then poll to get its final status. Servers that do not send events are polled
+    // from the start.
+    if !args.no_events {
+        // Running out of time is handled by the polling, which cancels the job
+        let followed =
+            tokio::time::timeout(timeout, follow_events(uuid, &client, &args.endpoint)).await;
+        if let Ok(Err(e)) = followed {
+            log::warn!(
+                "Could not follow the events of job {}, polling its status instead: {}",
+                uuid,
+                e
+            );
+        }
+    }
 
     loop {
         let elapsed = Instant::now() - start;
 
-        if elapsed >= Duration::from_millis(args.timeout) {
+        if elapsed >= timeout {
             log::error!(
                 "Failed to get trascription in {} seconds, canceling job...",
                 elapsed.as_secs()
@@ -86,12 +178,10 @@ async fn run_job(args: Args) -> Result<(), Box<dyn std::error::Error>> {
             }
         };
 
-        let get_status_resp = get_status_resp.json::<GetStatusResponse>().await?;
-
-        // Get the filename from the metadata, if not provided
-        if args.name.is_none() {
-            filename = get_filename_from_metadata(&get_status_resp.metadata)?;
-        }
+        let get_status_resp = check_response(get_status_resp)
+            .await?
+            .json::<GetStatusResponse>()
+            .await?;
 
         // If the status is finished, get the transcription file. Otherwise, wait and restart
         if get_status_resp.status.is_finished() {
@@ -106,45 +196,266 @@ async fn run_job(args: Args) -> Result<(), Box<dyn std::error::Error>> {
                 );
             }
 
-            get_job(uuid, filename, &client, &args).await?;
+            let formats = &get_status_resp.metadata.output_formats;
+            for format in formats.iter().copied() {
+                // Get the filename from the metadata, if not provided
+                let filename = match &args.name {
+                    Some(name) if formats.len() == 1 => name.clone(),
+                    Some(name) => PathBuf::from(name)
+                        .with_extension(format.as_str())
+                        .into_os_string(),
+                    None => get_filename_from_metadata(&get_status_resp.metadata, format)?,
+                };
+
+                get_job(uuid, format, filename, &client, &args).await?;
+            }
 
             break;
         }
 
+        match (get_status_resp.progress, get_status_resp.eta) {
+            (Some(progress), Some(eta)) => log::info!(
+                "Job {} reported status {:?}, {:.1}% done with about {} left, will retry in {} seconds",
+                uuid,
+                &get_status_resp.status,
+                progress,
+                format_duration(eta),
+                poll_interval.as_secs()
+            ),
+            (Some(progress), None) => log::info!(
+                "Job {} reported status {:?}, {:.1}% done, will retry in {} seconds",
+                uuid,
+                &get_status_resp.status,
+                progress,
+                poll_interval.as_secs()
+            ),
+            _ => log::info!(
+                "Job {} reported status {:?}, will retry in {} seconds",
+                uuid,
+                &get_status_resp.status,
+                poll_interval.as_secs()
+            ),
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+
+    Ok(())
+}
+
+/// Run a batch of jobs, wait for all of them to finish, and download the files they produced as a single archive.
+async fn run_batch(args: &Args, batch_args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+
+    tokio::fs::create_dir_all(&args.output_dir).await?;
+
+    let resp = client
+        .post(format!("{}/newBatch", &args.endpoint))
+        .json(&NewBatchRequest {
+            paths: batch_args.paths.clone(),
+            directory: batch_args.directory.clone(),
+            glob: batch_args.glob.clone(),
+            options: args.job_options(),
+        })
+        .send()
+        .await?;
+    let new_batch_resp = check_response(resp)
+        .await?
+        .json::<NewBatchResponse>()
+        .await?;
+
+    let uuid = new_batch_resp.uuid;
+    log::info!(
+        "Queued batch {} with {} jobs",
+        uuid,
+        new_batch_resp.jobs.len()
+    );
+
+    let poll_interval = Duration::from_millis(args.poll_interval);
+    let start = Instant::now();
+    let timeout = Duration::from_millis(args.timeout);
+
+    loop {
+        let elapsed = Instant::now() - start;
+
+        if elapsed >= timeout {
+            log::error!(
+                "Batch did not finish in {} seconds, canceling its jobs...",
+                elapsed.as_secs()
+            );
+
+            let resp = client
+                .post(format!("{}/cancelBatch", &args.endpoint))
+                .json(&CancelBatchRequest { uuid })
+                .send()
+                .await?;
+            check_response(resp).await?;
+
+            // The jobs that finished in time still have their files
+            break;
+        }
+
+        let resp = client
+            .get(format!("{}/getBatch", &args.endpoint))
+            .query(&[("uuid", &uuid.to_string())])
+            .send()
+            .await;
+
+        let resp = match resp {
+            Ok(r) => r,
+            Err(e) => {
+                log::warn!("Error calling /getBatch, retrying: {}", e);
+                tokio::time::sleep(poll_interval).await;
+                continue;
+            }
+        };
+
+        let batch = check_response(resp)
+            .await?
+            .json::<GetBatchResponse>()
+            .await?;
+
+        let counts = batch
+            .counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(status, count)| format!("{} {}", count, status))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if batch.finished {
+            log::info!("Batch {} is finished: {}", uuid, counts);
+            break;
+        }
+
         log::info!(
-            "Job {} reported status {:?}, will retry in {} seconds",
+            "Batch {} is {:.1}% done: {}, will retry in {} seconds",
             uuid,
-            &get_status_resp.status,
+            batch.progress,
+            counts,
             poll_interval.as_secs()
         );
 
         tokio::time::sleep(poll_interval).await;
     }
 
+    let resp = client
+        .get(format!("{}/getBatchArtifacts", &args.endpoint))
+        .query(&[("uuid", &uuid.to_string())])
+        .send()
+        .await?;
+    let bytes = match check_response(resp).await {
+        Ok(resp) => resp.bytes().await?,
+        Err(e) => {
+            log::warn!("No file to download: {}", e);
+            return Ok(());
+        }
+    };
+
+    let mut path = PathBuf::from(&args.output_dir);
+    path.push(
+        args.name
+            .clone()
+            .unwrap_or_else(|| OsString::from(format!("batch-{}.zip", uuid))),
+    );
+
+    log::info!("Saving archive to {:?}", path.as_path());
+
+    let mut file = tokio::fs::File::create(path.as_path()).await?;
+    file.write_all(&bytes).await?;
+
+    log::info!("Archive {:?} saved successfully", path.as_path());
+
     Ok(())
 }
 
+/// Follow the events of a job until it is finished.
+async fn follow_events(
+    uuid: Uuid,
+    client: &Client,
+    endpoint: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let resp = client
+        .get(format!("{}/getJobEvents", endpoint))
+        .query(&[("uuid", uuid.to_string())])
+        .send()
+        .await?;
+    let mut resp = check_response(resp).await?;
+
+    // Events are separated by a blank line, and may be split across chunks
+    let mut buffer = String::new();
+    while let Some(chunk) = resp.chunk().await? {
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+        while let Some(end) = buffer.find("\n\n") {
+            let block: String = buffer.drain(..end + 2).collect();
+            // Lines other than data are the name of the event, which is in the data as well, and comments
+            let data: String = block
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(str::trim_start)
+                .collect();
+            if data.is_empty() {
+                continue;
+            }
+
+            let event = serde_json::from_str::<JobEvent>(&data)?;
+            match (event.kind, event.progress, event.eta) {
+                (JobEventKind::Progress, Some(progress), Some(eta)) => log::info!(
+                    "Job {} is {:.1}% done with about {} left",
+                    uuid,
+                    progress,
+                    format_duration(eta)
+                ),
+                (JobEventKind::Progress, Some(progress), None) => {
+                    log::info!("Job {} is {:.1}% done", uuid, progress)
+                }
+                _ => log::info!("Job {} reported status {:?}", uuid, event.status),
+            }
+
+            if event.status.is_finished() {
+                return Ok(());
+            }
+        }
+    }
+
+    Err("The server stopped sending events before the job finished".into())
+}
+
+/// Format a number of seconds for humans, e.g. `1h05m` or `3m20s`.
+fn format_duration(secs: u64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{}h{:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m{:02}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 fn get_filename_from_metadata(
     metadata: &JobMetadata,
+    format: OutputFormat,
 ) -> Result<OsString, Box<dyn std::error::Error>> {
     let mut original_filename = PathBuf::from(metadata.filename.as_os_str());
-    original_filename.set_extension("srt");
+    original_filename.set_extension(format.as_str());
     Ok(original_filename.into_os_string())
 }
 
 async fn get_job(
     uuid: Uuid,
+    format: OutputFormat,
     filename: OsString,
     client: &Client,
     args: &Args,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut bytes = client
+    let resp = client
         .get(format!("{}/getJob", &args.endpoint))
-        .query(&[("uuid", &uuid.to_string())])
+        .query(&[("uuid", uuid.to_string()), ("format", format.to_string())])
         .send()
-        .await?
-        .bytes()
         .await?;
+    let bytes = check_response(resp).await?.bytes().await?;
 
     let mut path = PathBuf::new();
     path.push(&args.output_dir);
@@ -153,7 +464,7 @@ async fn get_job(
     log::info!("Saving file to {:?}", path.as_path());
 
     let mut file = tokio::fs::File::create(path.clone()).await?;
-    file.write_all(&mut bytes).await?;
+    file.write_all(&bytes).await?;
 
     log::info!("File {:?} saved successfully", path.as_path());
 
@@ -171,13 +482,65 @@ async fn cancel_job(
         .send()
         .await?;
 
-    if !cancel_job_resp.status().is_success() {
-        log::warn!(
-            "/cancelJob did not return a successful status code: {:?}",
-            cancel_job_resp
-        );
+    match check_response(cancel_job_resp).await {
+        Ok(resp) => log::info!("Response to /cancelJob: {resp:?}"),
+        Err(e) => log::warn!("{}", e),
+    }
+
+    Ok(())
+}
+
+async fn print_logs(endpoint: &str, args: &LogsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let client = reqwest::Client::new();
+
+    let mut query = vec![
+        ("uuid", args.uuid.to_string()),
+        ("stream", args.stream.to_string()),
+        ("follow", args.follow.to_string()),
+    ];
+    if let Some(tail) = args.tail {
+        query.push(("tail", tail.to_string()));
+    }
+
+    let resp = client
+        .get(format!("{}/getJobLogs", endpoint))
+        .query(&query)
+        .send()
+        .await?;
+    let mut resp = check_response(resp).await?;
+
+    let mut stdout = tokio::io::stdout();
+
+    // Following sends the new lines of the logs as they come
+    if args.follow {
+        while let Some(chunk) = resp.chunk().await? {
+            stdout.write_all(&chunk).await?;
+            stdout.flush().await?;
+        }
+        return Ok(());
+    }
+
+    let resp = resp.json::<GetJobLogsResponse>().await?;
+    for job_log in resp.logs {
+        write_log(&mut stdout, job_log.stream, &job_log.content).await?;
+    }
+
+    Ok(())
+}
+
+/// Print a log to the stream it came from.
+async fn write_log(
+    stdout: &mut Stdout,
+    stream: LogStream,
+    content: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if stream == LogStream::Stderr {
+        let mut stderr = tokio::io::stderr();
+        stderr.write_all(content.as_bytes()).await?;
+        stderr.flush().await?;
     } else {
-        log::info!("Response to /cancelJob: {cancel_job_resp:?}");
+        stdout.write_all(content.as_bytes()).await?;
+        stdout.flush().await?;
     }
 
     Ok(())
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{output_format::OutputFormat, NewJobOptions};
+
+/// Request object for queueing a job for every file of a list, or of a directory.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NewBatchRequest {
+    /// The paths of the files to transcribe, within the storage directory. Cannot be combined with `directory` and `glob`.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// The directory to look for files in, within the storage directory. Defaults to the storage directory when `glob` is set.
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// The pattern of the files to transcribe, relative to `directory`, e.g. `season-1/*.mkv` or `**/*.mp4`. Defaults to every file
+    /// directly in `directory`.
+    #[serde(default)]
+    pub glob: Option<String>,
+    /// How to run every job of the batch
+    #[serde(flatten)]
+    pub options: NewJobOptions,
+}
+
+/// Response object for queueing a batch.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NewBatchResponse {
+    /// The UUID of the batch
+    pub uuid: Uuid,
+    /// The UUIDs of the jobs of the batch, in the order of their files
+    pub jobs: Vec<Uuid>,
+}
+
+/// Request object for getting the status of a batch.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetBatchRequest {
+    /// The UUID of the batch.
+    pub uuid: Uuid,
+}
+
+/// Response object for getting the status of a batch.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetBatchResponse {
+    /// The UUID of the batch
+    pub uuid: Uuid,
+    /// The UUIDs of the jobs of the batch, oldest first
+    pub jobs: Vec<Uuid>,
+    /// The number of jobs of the batch with each status, by the name of the status
+    pub counts: BTreeMap<String, usize>,
+    /// How much of the batch is done, in percent. Finished jobs count as done, and running jobs as far as they got.
+    pub progress: f64,
+    /// Whether every job of the batch is finished
+    pub finished: bool,
+    /// When the batch was queued
+    pub created_at: DateTime<Utc>,
+    /// When a job of the batch last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request object for canceling the jobs of a batch.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CancelBatchRequest {
+    /// The UUID of the batch.
+    pub uuid: Uuid,
+}
+
+/// Response object for canceling the jobs of a batch.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CancelBatchResponse {
+    /// The UUIDs of the jobs that were canceled. Jobs that were already finished are left as they are.
+    pub canceled: Vec<Uuid>,
+}
+
+/// Request object for downloading the files produced by the jobs of a batch, as a ZIP archive.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetBatchArtifactsRequest {
+    /// The UUID of the batch.
+    pub uuid: Uuid,
+    /// Only include the files of this format. Defaults to every format the jobs produced.
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+}
+
+/// Query of `GET /v2/batches/<UUID>/artifacts`, which takes the UUID of the batch in its path.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct BatchArtifactsQuery {
+    /// Only include the files of this format. Defaults to every format the jobs produced.
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+}
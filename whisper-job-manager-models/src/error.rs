@@ -0,0 +1,110 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// What went wrong with a request, stable across versions so clients can act on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The request is malformed, e.g. its body or query cannot be parsed
+    InvalidRequest,
+    /// The file to transcribe cannot be found in the storage directory
+    InvalidPath,
+    /// The options of the job are invalid
+    InvalidJob,
+    /// The job cannot be found
+    JobNotFound,
+    /// The batch cannot be found
+    BatchNotFound,
+    /// The job is still queued or running
+    JobNotFinished,
+    /// The job is already finished
+    JobFinished,
+    /// The job did not ask for the output format
+    FormatNotRequested,
+    /// The job did not produce a file of the output format
+    OutputNotFound,
+    /// The log of the job cannot be read
+    LogNotFound,
+    /// The uploaded file is too large
+    PayloadTooLarge,
+    /// The uploaded file has a content type the server does not accept
+    UnsupportedMediaType,
+    /// The server is shutting down and does not accept new jobs
+    ShuttingDown,
+    /// Something went wrong on the server
+    Internal,
+    /// A code this version does not know about, sent by a newer server
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    /// The code as it is sent.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidRequest => "invalid_request",
+            ErrorCode::InvalidPath => "invalid_path",
+            ErrorCode::InvalidJob => "invalid_job",
+            ErrorCode::JobNotFound => "job_not_found",
+            ErrorCode::BatchNotFound => "batch_not_found",
+            ErrorCode::JobNotFinished => "job_not_finished",
+            ErrorCode::JobFinished => "job_finished",
+            ErrorCode::FormatNotRequested => "format_not_requested",
+            ErrorCode::OutputNotFound => "output_not_found",
+            ErrorCode::LogNotFound => "log_not_found",
+            ErrorCode::PayloadTooLarge => "payload_too_large",
+            ErrorCode::UnsupportedMediaType => "unsupported_media_type",
+            ErrorCode::ShuttingDown => "shutting_down",
+            ErrorCode::Internal => "internal",
+            ErrorCode::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Body of every response to a request that failed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ErrorResponse {
+    /// What went wrong
+    pub code: ErrorCode,
+    /// What went wrong, for humans
+    pub message: String,
+    /// More about the error, depending on its code, e.g. the formats a job asked for with `format_not_requested`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ErrorResponse {
+    /// An error without details.
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Add details to the error.
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)?;
+        if let Some(details) = &self.details {
+            write!(f, ": {}", details)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ErrorResponse {}
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::job_status::JobStatus;
+
+/// What happened to a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobEventKind {
+    /// The job was queued, or queued again for a retry
+    Queued,
+    /// The job started preprocessing or running
+    Started,
+    /// The running job transcribed more of its file
+    Progress,
+    /// An attempt of the job failed, and the job will be retried
+    Retrying,
+    /// The job finished successfully
+    Succeeded,
+    /// The job failed
+    Failed,
+    /// The job was canceled
+    Canceled,
+    /// The job waited in the queue for too long
+    Expired,
+}
+
+impl JobEventKind {
+    /// The kind of the event of a job changing to the given status.
+    pub fn for_status(status: &JobStatus) -> Self {
+        match status {
+            JobStatus::Queued => JobEventKind::Queued,
+            JobStatus::Preprocessing | JobStatus::Running => JobEventKind::Started,
+            JobStatus::Retrying { .. } => JobEventKind::Retrying,
+            JobStatus::Succeeded => JobEventKind::Succeeded,
+            JobStatus::Failed { .. } => JobEventKind::Failed,
+            JobStatus::Canceled => JobEventKind::Canceled,
+            JobStatus::Expired => JobEventKind::Expired,
+        }
+    }
+
+    /// The name of the kind, used as the name of server-sent events.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobEventKind::Queued => "queued",
+            JobEventKind::Started => "started",
+            JobEventKind::Progress => "progress",
+            JobEventKind::Retrying => "retrying",
+            JobEventKind::Succeeded => "succeeded",
+            JobEventKind::Failed => "failed",
+            JobEventKind::Canceled => "canceled",
+            JobEventKind::Expired => "expired",
+        }
+    }
+}
+
+impl fmt::Display for JobEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Something that happened to a job, as streamed by `/getJobEvents`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobEvent {
+    /// The UUID of the job.
+    pub uuid: Uuid,
+    /// What happened
+    pub kind: JobEventKind,
+    /// The status of the job after it happened
+    pub status: JobStatus,
+    /// How much of the file the job transcribed, in percent, with `progress` events
+    #[serde(default)]
+    pub progress: Option<f64>,
+    /// The estimated time left until the job finishes, in seconds, with `progress` events
+    #[serde(default)]
+    pub eta: Option<u64>,
+    /// When it happened
+    pub at: chrono::DateTime<Utc>,
+}
+
+impl JobEvent {
+    /// The event of a job changing to the given status.
+    pub fn status_changed(uuid: Uuid, status: JobStatus) -> Self {
+        JobEvent {
+            uuid,
+            kind: JobEventKind::for_status(&status),
+            status,
+            progress: None,
+            eta: None,
+            at: chrono::offset::Utc::now(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// An output stream of the process of a job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    /// The standard output
+    Stdout,
+    /// The standard error
+    Stderr,
+    /// Both the standard output and the standard error
+    #[default]
+    Both,
+}
+
+impl LogStream {
+    /// The name of the stream.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+            LogStream::Both => "both",
+        }
+    }
+
+    /// The single streams this stream stands for.
+    pub fn streams(&self) -> &'static [LogStream] {
+        match self {
+            LogStream::Stdout => &[LogStream::Stdout],
+            LogStream::Stderr => &[LogStream::Stderr],
+            LogStream::Both => &[LogStream::Stdout, LogStream::Stderr],
+        }
+    }
+}
+
+impl fmt::Display for LogStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LogStream {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stdout" => Ok(LogStream::Stdout),
+            "stderr" => Ok(LogStream::Stderr),
+            "both" => Ok(LogStream::Both),
+            _ => Err(format!(
+                "Unknown stream {:?}, expected stdout, stderr or both",
+                s
+            )),
+        }
+    }
+}
+
+/// Part of the log of a job.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobLog {
+    /// The stream the log comes from
+    pub stream: LogStream,
+    /// The content of the log
+    pub content: String,
+    /// Where the content starts in the log, in bytes
+    pub offset: u64,
+    /// Where the content ends in the log, in bytes, to pass as the offset to read what comes next
+    pub next_offset: u64,
+}
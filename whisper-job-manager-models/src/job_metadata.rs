@@ -2,6 +2,9 @@ use std::path::PathBuf;
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{output_format::OutputFormat, task::Task};
 
 /// Metadata on a job, including information about the file
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -9,6 +12,49 @@ pub struct JobMetadata {
     pub filename: PathBuf,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
+    /// The number of times the job started running
+    #[serde(default)]
+    pub attempts: u32,
+    /// When the job will be queued again after a failed attempt, if it will be
+    #[serde(default)]
+    pub next_retry_at: Option<chrono::DateTime<Utc>>,
+    /// The language the job runs with, or none if whisper detects it
+    #[serde(default)]
+    pub language: Option<String>,
+    /// The model the job runs with, once it is known. Once the job ran, this is the model whisper reported loading if it did.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// What whisper does with the speech
+    #[serde(default)]
+    pub task: Task,
+    /// The formats of the files the job produces
+    #[serde(default = "default_output_formats")]
+    pub output_formats: Vec<OutputFormat>,
+    /// The language whisper detected, as it names it, when the job let whisper detect the language
+    #[serde(default)]
+    pub detected_language: Option<String>,
+    /// The device the job ran on, once it is known
+    #[serde(default)]
+    pub device: Option<String>,
+    /// The duration of the file to transcribe, in seconds, if it could be found
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+    /// Where the part of the file to transcribe starts, in seconds, if only a part is transcribed
+    #[serde(default)]
+    pub start_secs: Option<f64>,
+    /// Where the part of the file to transcribe ends, in seconds, if only a part is transcribed
+    #[serde(default)]
+    pub end_secs: Option<f64>,
+    /// The labels the job was queued with
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The batch the job was queued with, if any
+    #[serde(default)]
+    pub batch_id: Option<Uuid>,
+}
+
+fn default_output_formats() -> Vec<OutputFormat> {
+    vec![OutputFormat::Srt]
 }
 
 impl JobMetadata {
@@ -18,6 +64,19 @@ impl JobMetadata {
             filename,
             created_at: chrono::offset::Utc::now(),
             updated_at: chrono::offset::Utc::now(),
+            attempts: 0,
+            next_retry_at: None,
+            language: None,
+            model: None,
+            task: Task::default(),
+            output_formats: default_output_formats(),
+            detected_language: None,
+            device: None,
+            duration_secs: None,
+            start_secs: None,
+            end_secs: None,
+            tags: Vec::new(),
+            batch_id: None,
         }
     }
 }
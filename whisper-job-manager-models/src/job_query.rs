@@ -0,0 +1,135 @@
+use std::{fmt, str::FromStr};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What jobs are sorted by when they are listed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    /// When the job was created
+    #[default]
+    CreatedAt,
+    /// When the job was last updated
+    UpdatedAt,
+}
+
+impl SortField {
+    /// The name of the field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortField::CreatedAt => "created_at",
+            SortField::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created_at" => Ok(SortField::CreatedAt),
+            "updated_at" => Ok(SortField::UpdatedAt),
+            _ => Err(format!(
+                "Unknown sort field {:?}, expected created_at or updated_at",
+                s
+            )),
+        }
+    }
+}
+
+/// In which order jobs are listed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    /// Oldest first
+    #[default]
+    Asc,
+    /// Newest first
+    Desc,
+}
+
+impl SortOrder {
+    /// The name of the order.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Unknown sort order {:?}, expected asc or desc", s)),
+        }
+    }
+}
+
+/// Request object for listing jobs, with `/getAllStatuses` or `/v2/jobs`. Every filter is optional, and only keeps the jobs matching it.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ListJobsRequest {
+    /// Comma-separated statuses, e.g. `Queued,Running`. Keeps the jobs with any of them.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Keeps the jobs whose filename contains this, ignoring case.
+    #[serde(default)]
+    pub filename: Option<String>,
+    /// Keeps the jobs created at or after this time.
+    #[serde(default)]
+    pub created_after: Option<chrono::DateTime<Utc>>,
+    /// Keeps the jobs created before this time.
+    #[serde(default)]
+    pub created_before: Option<chrono::DateTime<Utc>>,
+    /// Keeps the jobs updated at or after this time.
+    #[serde(default)]
+    pub updated_after: Option<chrono::DateTime<Utc>>,
+    /// Keeps the jobs updated before this time.
+    #[serde(default)]
+    pub updated_before: Option<chrono::DateTime<Utc>>,
+    /// Keeps the jobs running with this language, or for which whisper detected it.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Comma-separated tags. Keeps the jobs having all of them.
+    #[serde(default)]
+    pub tags: Option<String>,
+    /// Keeps the jobs of this batch.
+    #[serde(default)]
+    pub batch: Option<Uuid>,
+    /// What to sort the jobs by. Defaults to the creation time.
+    #[serde(default)]
+    pub sort: SortField,
+    /// In which order to sort the jobs. Defaults to oldest first.
+    #[serde(default)]
+    pub order: SortOrder,
+    /// The largest number of jobs to respond with.
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Where the page starts, as returned in `next_cursor` with the previous page. The other fields must not change between pages.
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// Split a comma-separated list of a query, ignoring blank items.
+pub fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
@@ -1,3 +1,4 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
 /// The status of a job.
@@ -5,6 +6,8 @@ use serde::{Deserialize, Serialize};
 pub enum JobStatus {
     /// The job is queued to run
     Queued,
+    /// The file of the job is being prepared with ffmpeg before it is transcribed
+    Preprocessing,
     /// The job is running
     Running,
     /// The job finished successfully
@@ -13,14 +16,51 @@ pub enum JobStatus {
     Canceled,
     /// The job failed with the given optional reason
     Failed { reason: Option<String> },
+    /// The job waited in the queue for longer than its maximum queue time, and will not run
+    Expired,
+    /// The given attempt of the job failed with the given optional reason, and the job will be queued again at the given time
+    Retrying {
+        attempt: u32,
+        next_retry_at: chrono::DateTime<Utc>,
+        reason: Option<String>,
+    },
 }
 
 impl JobStatus {
-    /// Check if the job is finished, i.e. it is not queued or running.
-    pub fn is_finished(&self) -> bool {
+    /// The names of every status, as they are serialized.
+    pub const NAMES: &'static [&'static str] = &[
+        "Queued",
+        "Preprocessing",
+        "Running",
+        "Succeeded",
+        "Canceled",
+        "Failed",
+        "Expired",
+        "Retrying",
+    ];
+
+    /// The name of the status without its details, as it is serialized.
+    pub fn name(&self) -> &'static str {
         match self {
-            JobStatus::Queued | JobStatus::Running => false,
-            _ => true,
+            JobStatus::Queued => "Queued",
+            JobStatus::Preprocessing => "Preprocessing",
+            JobStatus::Running => "Running",
+            JobStatus::Succeeded => "Succeeded",
+            JobStatus::Canceled => "Canceled",
+            JobStatus::Failed { .. } => "Failed",
+            JobStatus::Expired => "Expired",
+            JobStatus::Retrying { .. } => "Retrying",
         }
     }
+
+    /// Check if the job is finished, i.e. it is not queued, preprocessing, running or waiting to be retried.
+    pub fn is_finished(&self) -> bool {
+        !matches!(
+            self,
+            JobStatus::Queued
+                | JobStatus::Preprocessing
+                | JobStatus::Running
+                | JobStatus::Retrying { .. }
+        )
+    }
 }
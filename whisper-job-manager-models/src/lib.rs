@@ -1,10 +1,23 @@
+use job_log::{JobLog, LogStream};
 use job_metadata::JobMetadata;
 use job_status::JobStatus;
+use output_format::OutputFormat;
+use retry_policy::RetryPolicy;
 use serde::{Deserialize, Serialize};
+use task::Task;
 use uuid::Uuid;
 
+pub mod batch;
+pub mod error;
+pub mod job_event;
+pub mod job_log;
 pub mod job_metadata;
+pub mod job_query;
 pub mod job_status;
+pub mod output_format;
+pub mod retry_policy;
+pub mod task;
+pub mod webhook;
 
 /// Request object for canceling a job.
 #[derive(Debug, Deserialize, Serialize)]
@@ -18,6 +31,68 @@ pub struct CancelJobRequest {
 pub struct GetJobRequest {
     /// The UUID of the job.
     pub uuid: Uuid,
+    /// The format of the file to get. Defaults to the first format the job asked for.
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+}
+
+/// Request object for following the events of jobs.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct GetJobEventsRequest {
+    /// The UUID of the job to follow. Follows every job if not set.
+    #[serde(default)]
+    pub uuid: Option<Uuid>,
+}
+
+/// Request object for reading the logs of a job.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetJobLogsRequest {
+    /// The UUID of the job.
+    pub uuid: Uuid,
+    /// The stream to read. Defaults to both.
+    #[serde(default)]
+    pub stream: LogStream,
+    /// Only read the given number of lines at the end of the log.
+    #[serde(default)]
+    pub tail: Option<usize>,
+    /// Only read the log from the given byte offset, as returned by a previous read. Needs a single stream.
+    #[serde(default)]
+    pub offset: Option<u64>,
+    /// Keep sending new lines as plain text while the job runs, instead of responding with what was logged so far.
+    #[serde(default)]
+    pub follow: bool,
+}
+
+/// Response object for reading the logs of a job.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetJobLogsResponse {
+    /// The logs of every stream that was read
+    pub logs: Vec<JobLog>,
+}
+
+/// Request object for listing the files a job produced.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetArtifactsRequest {
+    /// The UUID of the job.
+    pub uuid: Uuid,
+}
+
+/// A file produced by a job.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Artifact {
+    /// The format of the file, to pass to `/getJob`
+    pub format: OutputFormat,
+    /// The name of the file
+    pub filename: String,
+    /// The size of the file, in bytes
+    pub size: u64,
+}
+
+/// Response object for listing the files a job produced.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetArtifactsResponse {
+    /// The files of the formats the job asked for, that the job produced
+    pub artifacts: Vec<Artifact>,
 }
 
 /// Response object for getting the status of all jobs.
@@ -25,6 +100,9 @@ pub struct GetJobRequest {
 pub struct GetAllStatusesResponse {
     /// The statuses of the jobs
     pub statuses: Vec<GetStatusResponse>,
+    /// The cursor to pass to get the next page, if there are more jobs
+    #[serde(default)]
+    pub next_cursor: Option<String>,
 }
 
 /// Request object for getting the status of a job.
@@ -37,10 +115,32 @@ pub struct GetStatusRequest {
 /// Response object for getting the status of a jobs.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GetStatusResponse {
+    /// The UUID of the job.
+    #[serde(default)]
+    pub uuid: Uuid,
     /// The status of the job
     pub status: JobStatus,
     /// The metadata of the job
     pub metadata: JobMetadata,
+    /// The effective priority of the job. While the job is queued, this includes any increase from waiting in the queue.
+    #[serde(default)]
+    pub priority: i32,
+    /// How much of the file a running job transcribed, in percent, when the duration of the file is known
+    #[serde(default)]
+    pub progress: Option<f64>,
+    /// The estimated time left until a running job finishes, in seconds, once it transcribed part of the file
+    #[serde(default)]
+    pub eta: Option<u64>,
+}
+
+/// Response object for listing jobs with the v2 API.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListJobsResponse {
+    /// The jobs of the page, in the order asked for
+    pub jobs: Vec<GetStatusResponse>,
+    /// The cursor to pass to get the next page, if there are more jobs
+    #[serde(default)]
+    pub next_cursor: Option<String>,
 }
 
 /// Request object for queueing a new job.
@@ -48,6 +148,58 @@ pub struct GetStatusResponse {
 pub struct NewJobRequest {
     /// The path to the file to transcribe. Must be within the storage directory specified on the server.
     pub path: String,
+    /// How to run the job
+    #[serde(flatten)]
+    pub options: NewJobOptions,
+}
+
+/// How to run a new job, whether its file is already on the server or uploaded with `/uploadJob`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NewJobOptions {
+    /// The priority of the job, higher runs first when the server uses a strategy with priorities. Defaults to 0.
+    #[serde(default)]
+    pub priority: Option<i32>,
+    /// Who submits the job, used to share the server fairly between submitters. Only used if the server trusts the submitters clients give,
+    /// the address of the client is used otherwise.
+    #[serde(default)]
+    pub submitter: Option<String>,
+    /// How the job is retried when it fails. Defaults to the retry policy of the server.
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// How long the job can run before it is killed and marked as failed, in seconds. Defaults to the limit of the server.
+    #[serde(default)]
+    pub max_runtime_secs: Option<u64>,
+    /// How long the job can wait in the queue before its first attempt, in seconds. The job expires after that. Defaults to the limit of the
+    /// server.
+    #[serde(default)]
+    pub max_queue_time_secs: Option<u64>,
+    /// The language spoken in the file, or `auto` to let whisper detect it. Defaults to the language configured on the server.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// The whisper model to run with. Defaults to the model chosen by the scheduler strategy of the server.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Whether to transcribe or translate the speech. Defaults to transcribing.
+    #[serde(default)]
+    pub task: Option<Task>,
+    /// The formats of the files to produce, `all` producing every format. Defaults to SRT only.
+    #[serde(default)]
+    pub output_formats: Option<Vec<OutputFormat>>,
+    /// Whether to extract and normalize the audio of the file with ffmpeg before transcribing it. Defaults to the setting of the server.
+    #[serde(default)]
+    pub preprocess: Option<bool>,
+    /// Only transcribe the file from this time on, in seconds. Timestamps of the transcript still match the whole file. Needs preprocessing.
+    #[serde(default)]
+    pub start_secs: Option<f64>,
+    /// Only transcribe the file up to this time, in seconds. Needs preprocessing.
+    #[serde(default)]
+    pub end_secs: Option<f64>,
+    /// Labels to find the job by when listing jobs. Defaults to none.
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// An http or https URL that is POSTed the outcome of the job once it finishes, besides the webhooks configured on the server
+    #[serde(default)]
+    pub callback_url: Option<String>,
 }
 
 /// Response object for queueing a new job.
@@ -0,0 +1,101 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// A format of the files whisper writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// SubRip subtitles
+    Srt,
+    /// WebVTT subtitles
+    Vtt,
+    /// Plain text, without timestamps
+    Txt,
+    /// Tab-separated values, with timestamps in milliseconds
+    Tsv,
+    /// JSON, with the details of every segment
+    Json,
+    /// Every format above
+    All,
+}
+
+impl OutputFormat {
+    /// Every format of file whisper can write.
+    pub const FILE_FORMATS: [OutputFormat; 5] = [
+        OutputFormat::Srt,
+        OutputFormat::Vtt,
+        OutputFormat::Txt,
+        OutputFormat::Tsv,
+        OutputFormat::Json,
+    ];
+
+    /// The name of the format as whisper expects it, which is also the extension of the files in this format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Srt => "srt",
+            OutputFormat::Vtt => "vtt",
+            OutputFormat::Txt => "txt",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Json => "json",
+            OutputFormat::All => "all",
+        }
+    }
+
+    /// The content type of files in this format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Srt => "application/x-subrip",
+            OutputFormat::Vtt => "text/vtt",
+            OutputFormat::Txt => "text/plain",
+            OutputFormat::Tsv => "text/tab-separated-values",
+            OutputFormat::Json => "application/json",
+            OutputFormat::All => "application/octet-stream",
+        }
+    }
+
+    /// Replace `All` with every format of file, and remove duplicates while keeping the order.
+    pub fn expand(formats: &[OutputFormat]) -> Vec<OutputFormat> {
+        let mut expanded = Vec::with_capacity(OutputFormat::FILE_FORMATS.len());
+
+        for format in formats {
+            let formats: &[OutputFormat] = match format {
+                OutputFormat::All => &OutputFormat::FILE_FORMATS,
+                f => std::slice::from_ref(f),
+            };
+
+            for f in formats {
+                if !expanded.contains(f) {
+                    expanded.push(*f);
+                }
+            }
+        }
+
+        expanded
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "srt" => Ok(OutputFormat::Srt),
+            "vtt" => Ok(OutputFormat::Vtt),
+            "txt" => Ok(OutputFormat::Txt),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "json" => Ok(OutputFormat::Json),
+            "all" => Ok(OutputFormat::All),
+            _ => Err(format!(
+                "Unknown output format {:?}, expected one of srt, vtt, txt, tsv, json or all",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// The kind of failure of a job, used to decide whether the job is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FailureKind {
+    /// The process exited with a non-zero exit code
+    NonZeroExit,
+    /// The process was killed by a signal it did not get from the server, e.g. when running out of memory
+    Killed,
+    /// The process could not be started
+    StartFailed,
+    /// The exit of the process was never recorded
+    Lost,
+}
+
+impl FailureKind {
+    /// All kinds of failures.
+    pub const ALL: [FailureKind; 4] = [
+        FailureKind::NonZeroExit,
+        FailureKind::Killed,
+        FailureKind::StartFailed,
+        FailureKind::Lost,
+    ];
+}
+
+/// How a job is retried when it fails. Fields that are not set use the defaults of the server.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RetryPolicy {
+    /// The maximum number of times the job runs, including the first attempt. `1` disables retries.
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    /// How long to wait before the first retry, in seconds
+    #[serde(default)]
+    pub initial_backoff_secs: Option<u64>,
+    /// How much the wait grows with every retry
+    #[serde(default)]
+    pub backoff_multiplier: Option<f64>,
+    /// The longest wait between two attempts, in seconds
+    #[serde(default)]
+    pub max_backoff_secs: Option<u64>,
+    /// The kinds of failures that are retried
+    #[serde(default)]
+    pub retry_on: Option<Vec<FailureKind>>,
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{job_metadata::JobMetadata, job_status::JobStatus, Artifact};
+
+/// The event webhooks are called for, sent in the `X-Whisper-Event` header.
+pub const JOB_FINISHED_EVENT: &str = "job.finished";
+
+/// Header holding the HMAC-SHA256 signature of the body, as `sha256=<hex digest>`, when the server has a webhook secret.
+pub const SIGNATURE_HEADER: &str = "X-Whisper-Signature";
+
+/// Header holding the name of the event.
+pub const EVENT_HEADER: &str = "X-Whisper-Event";
+
+/// Header holding the UUID of the delivery, which stays the same when a delivery is retried.
+pub const DELIVERY_HEADER: &str = "X-Whisper-Delivery";
+
+/// Body POSTed to webhooks when a job finishes.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookPayload {
+    /// The UUID of the job
+    pub uuid: Uuid,
+    /// The final status of the job
+    pub status: JobStatus,
+    /// The metadata of the job
+    pub metadata: JobMetadata,
+    /// The files the job produced, with where to download them
+    pub artifacts: Vec<ArtifactLink>,
+}
+
+/// A file produced by a job, with where to download it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArtifactLink {
+    /// The file
+    #[serde(flatten)]
+    pub artifact: Artifact,
+    /// The URL of the file on the server
+    pub url: String,
+}
+
+/// An attempt at calling a webhook.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookDelivery {
+    /// The UUID of the job
+    pub uuid: Uuid,
+    /// The UUID of the delivery, shared by its attempts
+    pub delivery: Uuid,
+    /// The URL that was called
+    pub url: String,
+    /// The number of the attempt, starting at 1
+    pub attempt: u32,
+    /// When the attempt ended
+    pub at: DateTime<Utc>,
+    /// The status code the webhook responded with, if it responded
+    #[serde(default)]
+    pub status_code: Option<u16>,
+    /// Why the attempt failed, if it did
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Whether the webhook accepted the payload
+    pub delivered: bool,
+}
+
+/// Request object for reading the webhook deliveries of a job.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetWebhookDeliveriesRequest {
+    /// The UUID of the job.
+    pub uuid: Uuid,
+}
+
+/// Response object for reading the webhook deliveries of a job.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GetWebhookDeliveriesResponse {
+    /// Every attempt at calling a webhook for the job, oldest first
+    pub deliveries: Vec<WebhookDelivery>,
+}
@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Error, Result};
+use serde::Deserialize;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::scheduler::job_spec::JobSpec;
+
+use super::{
+    openai_whisper::{OpenAiWhisperBackend, OpenAiWhisperParams},
+    TranscriptionBackend,
+};
+
+/// The name of the program bundled with the server that stands in for whisper
+const FAKE_WHISPER_PROGRAM: &str = "fake-whisper";
+
+/// Parameters of the fake backend in the config.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct FakeBackendParams {
+    /// The program to run. If not set, the `fake-whisper` program next to the server.
+    pub command: Option<PathBuf>,
+    /// How long a run takes, in seconds
+    pub duration_secs: f64,
+    /// The number of segments of the transcript, printed as the run goes on
+    pub segments: u32,
+    /// The length of each segment, in seconds
+    pub segment_secs: f64,
+    /// The language reported when the job lets whisper detect it, as whisper names it
+    pub detected_language: String,
+    /// If set, every run exits with this code instead of writing a transcript
+    pub exit_code: Option<u8>,
+    /// Whether every run hangs instead of exiting, until it is canceled or times out
+    pub hang: bool,
+    /// Whether the TSV transcript has timestamps that cannot be read, e.g. to fail shifting them
+    pub malformed_tsv: bool,
+}
+
+impl Default for FakeBackendParams {
+    fn default() -> Self {
+        Self {
+            command: None,
+            duration_secs: 5.0,
+            segments: 5,
+            segment_secs: 5.0,
+            detected_language: String::from("English"),
+            exit_code: None,
+            hang: false,
+            malformed_tsv: false,
+        }
+    }
+}
+
+/// Backend running `fake-whisper`, a program bundled with the server that takes the arguments of openai-whisper and writes a
+/// made up transcript after a while. It lets the server run on machines without whisper, for tests and demos.
+#[derive(Debug)]
+pub struct FakeBackend {
+    params: FakeBackendParams,
+    /// Builds the arguments, which are the same as the ones of openai-whisper
+    whisper: OpenAiWhisperBackend,
+}
+
+impl FakeBackend {
+    /// Create the backend from its parameters in the config.
+    pub fn from_params(params: serde_json::Value) -> Result<Box<dyn TranscriptionBackend>> {
+        let params: FakeBackendParams = super::parse_params(params)?;
+
+        if params.duration_secs.is_nan() || params.duration_secs < 0.0 {
+            return Err(Error::msg("durationSecs must not be negative"));
+        }
+
+        if params.segment_secs.is_nan() || params.segment_secs <= 0.0 {
+            return Err(Error::msg("segmentSecs must be greater than 0"));
+        }
+
+        let command = match &params.command {
+            Some(c) => c.clone(),
+            None => std::env::current_exe()
+                .context("Cannot find the directory of the server")?
+                .with_file_name(FAKE_WHISPER_PROGRAM),
+        };
+
+        Ok(Box::new(FakeBackend {
+            params,
+            whisper: OpenAiWhisperBackend::new(OpenAiWhisperParams {
+                command: command.to_string_lossy().into_owned(),
+                extra_args: vec![],
+            }),
+        }))
+    }
+}
+
+impl TranscriptionBackend for FakeBackend {
+    fn build_command(&self, uuid: Uuid, spec: &JobSpec) -> std::io::Result<Command> {
+        let mut cmd = self.whisper.build_command(uuid, spec)?;
+
+        // See src/bin/fake-whisper.rs
+        cmd.env(
+            "FAKE_WHISPER_DURATION_SECS",
+            self.params.duration_secs.to_string(),
+        )
+        .env("FAKE_WHISPER_SEGMENTS", self.params.segments.to_string())
+        .env(
+            "FAKE_WHISPER_SEGMENT_SECS",
+            self.params.segment_secs.to_string(),
+        )
+        .env(
+            "FAKE_WHISPER_DETECTED_LANGUAGE",
+            &self.params.detected_language,
+        );
+
+        if let Some(code) = self.params.exit_code {
+            cmd.env("FAKE_WHISPER_EXIT_CODE", code.to_string());
+        }
+
+        if self.params.hang {
+            cmd.env("FAKE_WHISPER_HANG", "1");
+        }
+
+        if self.params.malformed_tsv {
+            cmd.env("FAKE_WHISPER_MALFORMED_TSV", "1");
+        }
+
+        Ok(cmd)
+    }
+}
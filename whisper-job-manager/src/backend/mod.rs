@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Error, Result};
+use serde::de::DeserializeOwned;
+use tokio::process::Command;
+use uuid::Uuid;
+use whisper_job_manager_models::output_format::OutputFormat;
+
+use crate::{config::BackendConfig, scheduler::job_spec::JobSpec, workspace};
+
+pub mod fake;
+pub mod faster_whisper;
+pub mod openai_whisper;
+pub mod whisper_cpp;
+
+/// A program transcribing files, which turns job specs into processes and knows where they write their outputs.
+pub trait TranscriptionBackend: std::fmt::Debug + Send + Sync {
+    /// Build the command running the job with the given UUID, writing its outputs to the workspace of the job. The output
+    /// streams of the command are set up by the caller.
+    fn build_command(&self, uuid: Uuid, spec: &JobSpec) -> std::io::Result<Command>;
+
+    /// Whether the backend can write files in the given format.
+    fn supports_format(&self, _format: OutputFormat) -> bool {
+        true
+    }
+
+    /// Find the file of the given format that the job with the given UUID wrote, if there is one.
+    fn find_output(&self, uuid: Uuid, format: OutputFormat) -> Option<PathBuf> {
+        workspace::find_artifact(uuid, format)
+    }
+}
+
+/// Function creating a backend from its parameters in the config.
+type BackendFactory = fn(serde_json::Value) -> Result<Box<dyn TranscriptionBackend>>;
+
+/// All backends that can be selected in the config, by name.
+const BACKENDS: &[(&str, BackendFactory)] = &[
+    (
+        "openaiWhisper",
+        openai_whisper::OpenAiWhisperBackend::from_params,
+    ),
+    ("whisperCpp", whisper_cpp::WhisperCppBackend::from_params),
+    (
+        "fasterWhisper",
+        faster_whisper::FasterWhisperBackend::from_params,
+    ),
+    ("fake", fake::FakeBackend::from_params),
+];
+
+/// Create the backend selected in the config, with the parameters given in the config.
+pub fn build_backend(config: &BackendConfig) -> Result<Box<dyn TranscriptionBackend>> {
+    let factory = BACKENDS
+        .iter()
+        .find(|(name, _)| *name == config.name)
+        .map(|(_, factory)| factory);
+
+    let Some(factory) = factory else {
+        let names: Vec<&str> = BACKENDS.iter().map(|(name, _)| *name).collect();
+        return Err(Error::msg(format!(
+            "Unknown transcription backend \"{}\", expected one of: {}",
+            config.name,
+            names.join(", ")
+        )));
+    };
+
+    factory(config.params.clone())
+        .with_context(|| format!("Invalid parameters for backend \"{}\"", config.name))
+}
+
+/// Parse the parameters of a backend, treating missing parameters as an empty object.
+fn parse_params<T: DeserializeOwned>(params: serde_json::Value) -> Result<T> {
+    let params = match params {
+        serde_json::Value::Null => serde_json::Value::Object(serde_json::Map::new()),
+        p => p,
+    };
+
+    Ok(serde_json::from_value(params)?)
+}
+
+/// The output format to pass to CLIs that only take one, like openai-whisper. They write every format when the job asks for more than one.
+fn single_output_format(spec: &JobSpec) -> OutputFormat {
+    match spec.output_formats.as_slice() {
+        [format] => *format,
+        _ => OutputFormat::All,
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::{scheduler::job_spec::JobSpec, workspace};
+
+use super::TranscriptionBackend;
+
+/// Parameters of the openai-whisper backend in the config.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct OpenAiWhisperParams {
+    /// The program to run
+    pub command: String,
+    /// Arguments added to every command, before the file to transcribe
+    pub extra_args: Vec<String>,
+}
+
+impl Default for OpenAiWhisperParams {
+    fn default() -> Self {
+        Self {
+            command: String::from("whisper"),
+            extra_args: vec![],
+        }
+    }
+}
+
+/// Backend running the `whisper` CLI of openai-whisper.
+#[derive(Debug)]
+pub struct OpenAiWhisperBackend {
+    params: OpenAiWhisperParams,
+}
+
+impl OpenAiWhisperBackend {
+    pub fn new(params: OpenAiWhisperParams) -> Self {
+        OpenAiWhisperBackend { params }
+    }
+
+    /// Create the backend from its parameters in the config.
+    pub fn from_params(params: serde_json::Value) -> Result<Box<dyn TranscriptionBackend>> {
+        Ok(Box::new(OpenAiWhisperBackend::new(super::parse_params(
+            params,
+        )?)))
+    }
+}
+
+impl TranscriptionBackend for OpenAiWhisperBackend {
+    fn build_command(&self, uuid: Uuid, spec: &JobSpec) -> std::io::Result<Command> {
+        let mut cmd = Command::new(&self.params.command);
+        cmd.arg("--output_dir")
+            .arg(workspace::workspace_path(uuid))
+            .arg("--output_format")
+            .arg(super::single_output_format(spec).as_str())
+            .arg("--task")
+            .arg(spec.task.as_str());
+
+        if let Some(language) = &spec.language {
+            cmd.arg("--language").arg(language);
+        }
+
+        if let Some(model) = &spec.model {
+            cmd.arg("--model").arg(model);
+        }
+
+        if let Some(device) = &spec.device {
+            cmd.arg("--device").arg(device);
+        }
+
+        if let Some(threads) = spec.threads {
+            cmd.arg("--threads").arg(threads.to_string());
+        }
+
+        cmd.args(&self.params.extra_args)
+            .arg(spec.source.as_path())
+            // Whisper is a Python program, which would only write its piped output in large blocks otherwise
+            .env("PYTHONUNBUFFERED", "1");
+
+        Ok(cmd)
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::process::Command;
+use uuid::Uuid;
+use whisper_job_manager_models::{output_format::OutputFormat, task::Task};
+
+use crate::{scheduler::job_spec::JobSpec, workspace};
+
+use super::TranscriptionBackend;
+
+/// Parameters of the whisper.cpp backend in the config.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct WhisperCppParams {
+    /// The program to run, `whisper-cli` or `main` for older versions
+    pub command: String,
+    /// The directory of the `ggml-<model>.bin` model files
+    pub models_dir: PathBuf,
+    /// Arguments added to every command, before the file to transcribe
+    pub extra_args: Vec<String>,
+}
+
+impl Default for WhisperCppParams {
+    fn default() -> Self {
+        Self {
+            command: String::from("whisper-cli"),
+            models_dir: PathBuf::from("./models"),
+            extra_args: vec![],
+        }
+    }
+}
+
+/// Backend running the CLI of whisper.cpp. It only reads WAV files unless it was built with FFmpeg, and cannot write TSV files.
+#[derive(Debug)]
+pub struct WhisperCppBackend {
+    params: WhisperCppParams,
+}
+
+impl WhisperCppBackend {
+    /// Create the backend from its parameters in the config.
+    pub fn from_params(params: serde_json::Value) -> Result<Box<dyn TranscriptionBackend>> {
+        Ok(Box::new(WhisperCppBackend {
+            params: super::parse_params(params)?,
+        }))
+    }
+
+    /// The flag making whisper.cpp write a file in the given format.
+    fn output_flag(format: OutputFormat) -> Option<&'static str> {
+        match format {
+            OutputFormat::Srt => Some("--output-srt"),
+            OutputFormat::Vtt => Some("--output-vtt"),
+            OutputFormat::Txt => Some("--output-txt"),
+            OutputFormat::Json => Some("--output-json"),
+            OutputFormat::Tsv | OutputFormat::All => None,
+        }
+    }
+}
+
+impl TranscriptionBackend for WhisperCppBackend {
+    fn build_command(&self, uuid: Uuid, spec: &JobSpec) -> std::io::Result<Command> {
+        // Files are named after the transcribed file, like the other backends do
+        let stem = spec.source.file_stem().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{:?} has no file name", spec.source),
+            )
+        })?;
+
+        let mut cmd = Command::new(&self.params.command);
+        cmd.arg("--output-file")
+            .arg(workspace::workspace_file(uuid, &stem.to_string_lossy()));
+
+        for format in spec.output_formats.iter() {
+            let Some(flag) = Self::output_flag(*format) else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("whisper.cpp cannot write {} files", format),
+                ));
+            };
+            cmd.arg(flag);
+        }
+
+        if spec.task == Task::Translate {
+            cmd.arg("--translate");
+        }
+
+        // whisper.cpp detects the language with "auto", and defaults to English otherwise
+        cmd.arg("--language")
+            .arg(spec.language.as_deref().unwrap_or("auto"));
+
+        if let Some(model) = &spec.model {
+            let mut model_path = self.params.models_dir.clone();
+            model_path.push(format!("ggml-{}.bin", model));
+            cmd.arg("--model").arg(model_path);
+        }
+
+        if spec.device.as_deref() == Some("cpu") {
+            cmd.arg("--no-gpu");
+        }
+
+        if let Some(threads) = spec.threads {
+            cmd.arg("--threads").arg(threads.to_string());
+        }
+
+        cmd.args(&self.params.extra_args)
+            .arg("--file")
+            .arg(spec.source.as_path());
+
+        Ok(cmd)
+    }
+
+    fn supports_format(&self, format: OutputFormat) -> bool {
+        Self::output_flag(format).is_some()
+    }
+}
@@ -0,0 +1,286 @@
+//! Stand-in for the `whisper` CLI of openai-whisper, used by the `fake` transcription backend to run the server without
+//! whisper. It takes the same arguments, prints the same kind of output, and writes a deterministic transcript of every
+//! file after a while. What it does is set with the environment variables below.
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+/// How long a run takes in seconds, spread over the segments
+const DURATION_SECS_VAR: &str = "FAKE_WHISPER_DURATION_SECS";
+/// The number of segments of the transcript
+const SEGMENTS_VAR: &str = "FAKE_WHISPER_SEGMENTS";
+/// The length of each segment in seconds
+const SEGMENT_SECS_VAR: &str = "FAKE_WHISPER_SEGMENT_SECS";
+/// The language reported when no language is given, as whisper names it
+const DETECTED_LANGUAGE_VAR: &str = "FAKE_WHISPER_DETECTED_LANGUAGE";
+/// If set, exit with this code after the segments instead of writing the transcript
+const EXIT_CODE_VAR: &str = "FAKE_WHISPER_EXIT_CODE";
+/// If set to `1`, never exit after the segments
+const HANG_VAR: &str = "FAKE_WHISPER_HANG";
+/// If set to `1`, write the timestamps of the TSV transcript as `mm:ss.mmm` instead of milliseconds, which cannot be read back
+const MALFORMED_TSV_VAR: &str = "FAKE_WHISPER_MALFORMED_TSV";
+
+/// The arguments of whisper that change what is written.
+#[derive(Debug)]
+struct Args {
+    files: Vec<PathBuf>,
+    output_dir: PathBuf,
+    output_format: String,
+    task: String,
+    language: Option<String>,
+    device: Option<String>,
+}
+
+impl Args {
+    /// Parse the arguments the way whisper does. Every option takes a value, options this program ignores included.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            files: vec![],
+            output_dir: PathBuf::from("."),
+            output_format: String::from("all"),
+            task: String::from("transcribe"),
+            language: None,
+            device: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                parsed.files.push(PathBuf::from(arg));
+                continue;
+            };
+
+            let value = args
+                .next()
+                .ok_or_else(|| format!("argument --{}: expected one argument", option))?;
+
+            match option {
+                "output_dir" => parsed.output_dir = PathBuf::from(value),
+                "output_format" => parsed.output_format = value,
+                "task" => parsed.task = value,
+                "language" => parsed.language = Some(value),
+                "device" => parsed.device = Some(value),
+                _ => {}
+            }
+        }
+
+        if parsed.files.is_empty() {
+            return Err(String::from("the following arguments are required: audio"));
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Read a number from the environment, or use the default if it is not set.
+fn env_number<T: std::str::FromStr>(name: &str, default: T) -> Result<T, String> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("{} is not a valid number: {:?}", name, value)),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Format a time in seconds as `hh:mm:ss` followed by the separator and the milliseconds.
+fn format_timestamp(secs: f64, separator: char, always_include_hours: bool) -> String {
+    let millis = (secs * 1000.0).round() as u64;
+    let (hours, minutes, seconds, millis) = (
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+    );
+
+    if always_include_hours || hours > 0 {
+        format!(
+            "{:02}:{:02}:{:02}{}{:03}",
+            hours, minutes, seconds, separator, millis
+        )
+    } else {
+        format!("{:02}:{:02}{}{:03}", minutes, seconds, separator, millis)
+    }
+}
+
+/// A segment of the transcript.
+struct Segment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// Write the transcript in one format to the output directory, named after the transcribed file.
+fn write_transcript(
+    args: &Args,
+    file: &Path,
+    format: &str,
+    segments: &[Segment],
+    language: &str,
+    malformed_tsv: bool,
+) -> std::io::Result<()> {
+    let content = match format {
+        "srt" => segments
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    i + 1,
+                    format_timestamp(s.start, ',', true),
+                    format_timestamp(s.end, ',', true),
+                    s.text
+                )
+            })
+            .collect::<String>(),
+        "vtt" => {
+            let cues: String = segments
+                .iter()
+                .map(|s| {
+                    format!(
+                        "{} --> {}\n{}\n\n",
+                        format_timestamp(s.start, '.', false),
+                        format_timestamp(s.end, '.', false),
+                        s.text
+                    )
+                })
+                .collect();
+            format!("WEBVTT\n\n{}", cues)
+        }
+        "txt" => segments.iter().map(|s| format!("{}\n", s.text)).collect(),
+        "tsv" => {
+            let rows: String = segments
+                .iter()
+                .map(|s| {
+                    if malformed_tsv {
+                        format!(
+                            "{}\t{}\t{}\n",
+                            format_timestamp(s.start, '.', false),
+                            format_timestamp(s.end, '.', false),
+                            s.text
+                        )
+                    } else {
+                        format!(
+                            "{}\t{}\t{}\n",
+                            (s.start * 1000.0).round(),
+                            (s.end * 1000.0).round(),
+                            s.text
+                        )
+                    }
+                })
+                .collect();
+            format!("start\tend\ttext\n{}", rows)
+        }
+        "json" => {
+            let text: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+            let segments: Vec<serde_json::Value> = segments
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    serde_json::json!({"id": i, "start": s.start, "end": s.end, "text": s.text})
+                })
+                .collect();
+            serde_json::json!({"text": text.join(" "), "segments": segments, "language": language})
+                .to_string()
+        }
+        _ => return Ok(()),
+    };
+
+    let stem = file.file_stem().unwrap_or(file.as_os_str());
+    let mut path = args.output_dir.join(stem);
+    path.set_extension(format);
+    std::fs::write(path, content)
+}
+
+fn run() -> Result<ExitCode, String> {
+    let args = Args::parse(std::env::args().skip(1))?;
+
+    let duration_secs: f64 = env_number(DURATION_SECS_VAR, 5.0)?;
+    let segment_count: u32 = env_number(SEGMENTS_VAR, 5)?;
+    let segment_secs: f64 = env_number(SEGMENT_SECS_VAR, 5.0)?;
+    let exit_code: Option<u8> = match std::env::var(EXIT_CODE_VAR) {
+        Ok(_) => Some(env_number(EXIT_CODE_VAR, 1)?),
+        Err(_) => None,
+    };
+    let hang = std::env::var(HANG_VAR).is_ok_and(|v| v == "1");
+    let malformed_tsv = std::env::var(MALFORMED_TSV_VAR).is_ok_and(|v| v == "1");
+    let pause = Duration::from_secs_f64(duration_secs.max(0.0) / segment_count.max(1) as f64);
+
+    if args.device.as_deref() != Some("cuda") {
+        eprintln!("UserWarning: FP16 is not supported on CPU; using FP32 instead");
+    }
+
+    for file in args.files.iter() {
+        let language = match &args.language {
+            Some(l) => l.clone(),
+            None => {
+                let detected = std::env::var(DETECTED_LANGUAGE_VAR)
+                    .unwrap_or_else(|_| String::from("English"));
+                println!("Detecting language using up to the first 30 seconds. Use `--language` to specify the language");
+                println!("Detected language: {}", detected);
+                detected
+            }
+        };
+
+        let verb = if args.task == "translate" {
+            "translated"
+        } else {
+            "transcribed"
+        };
+
+        let name = file
+            .file_name()
+            .unwrap_or(file.as_os_str())
+            .to_string_lossy();
+        let mut segments = Vec::with_capacity(segment_count as usize);
+        for i in 0..segment_count {
+            std::thread::sleep(pause);
+            let segment = Segment {
+                start: i as f64 * segment_secs,
+                end: (i + 1) as f64 * segment_secs,
+                text: format!("Segment {} {} from {}.", i + 1, verb, name),
+            };
+            println!(
+                "[{} --> {}]  {}",
+                format_timestamp(segment.start, '.', false),
+                format_timestamp(segment.end, '.', false),
+                segment.text
+            );
+            segments.push(segment);
+        }
+
+        if hang {
+            eprintln!("Hanging as asked by {}", HANG_VAR);
+            loop {
+                std::thread::sleep(Duration::from_secs(3600));
+            }
+        }
+
+        if let Some(code) = exit_code {
+            eprintln!("RuntimeError: failing as asked by {}", EXIT_CODE_VAR);
+            return Ok(ExitCode::from(code));
+        }
+
+        let formats: &[&str] = match args.output_format.as_str() {
+            "all" => &["txt", "vtt", "srt", "tsv", "json"],
+            f => &[f],
+        };
+        for format in formats {
+            write_transcript(&args, file, format, &segments, &language, malformed_tsv)
+                .map_err(|e| format!("Could not write the {} transcript: {}", format, e))?;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("fake-whisper: error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -1,31 +1,702 @@
-use std::path::{PathBuf, Path};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use serde::Deserialize;
+use anyhow::{Context, Error, Result};
+use serde::{Deserialize, Serialize};
+use whisper_job_manager_models::retry_policy::{FailureKind, RetryPolicy};
 
+use crate::constants::{AUTO_DETECT_LANGUAGE, WHISPER_LANGUAGES, WHISPER_MODELS};
+
+const DEFAULT_JOB_STORE_PATH: &str = "./jobs.jsonl";
+const DEFAULT_SCHEDULER_TICK_MILLIS: u64 = 1000 * 30;
+const DEFAULT_SCHEDULER_STRATEGY: &str = "simple";
+const DEFAULT_BACKEND: &str = "openaiWhisper";
+const DEFAULT_WEBHOOK_DELIVERY_LOG_PATH: &str = "./webhook_deliveries.jsonl";
+/// The longest a job or a webhook delivery can be made to wait between two attempts, in seconds
+const MAX_RETRY_BACKOFF_SECS: u64 = 60 * 60 * 24 * 7;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub video_storage_path: String,
     pub host: String,
-    pub port: u16
+    pub port: u16,
+    /// Path of the journal that jobs are persisted to, so they survive a restart
+    #[serde(default = "default_job_store_path")]
+    pub job_store_path: String,
+    /// How to reconcile the job store with the workspaces left on disk when the server starts
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
+    /// Configuration of the scheduler, which can be reloaded while the server runs
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    /// How failed jobs are retried, unless the job asks for something else
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// Time limits of jobs, unless the job asks for something else
+    #[serde(default)]
+    pub timeouts: TimeoutConfig,
+    /// What to do with running jobs when the server is asked to stop
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    /// The languages and models jobs can ask for
+    #[serde(default)]
+    pub transcription: TranscriptionConfig,
+    /// The program transcribing files
+    #[serde(default)]
+    pub backend: BackendConfig,
+    /// Limits of files uploaded with `/uploadJob`, and how long they are kept
+    #[serde(default)]
+    pub uploads: UploadConfig,
+    /// How files are prepared with ffmpeg before they are transcribed
+    #[serde(default)]
+    pub preprocessing: PreprocessingConfig,
+    /// Where to POST the outcome of jobs once they finish, and how
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    /// Limits of batches queued with `/newBatch`
+    #[serde(default)]
+    pub batches: BatchConfig,
+    /// How to tell who submits a job, which the `fairShare` strategy shares the server between
+    #[serde(default)]
+    pub submitters: SubmitterConfig,
+}
+
+impl Config {
+    /// The URL clients reach the server at, without a trailing slash.
+    pub fn public_url(&self) -> String {
+        match &self.webhooks.public_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://{}:{}", self.host, self.port),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerConfig {
+    /// The scheduler runs whenever something happens to a job, and at least once every period, in milliseconds
+    #[serde(default = "default_scheduler_tick_millis")]
+    pub tick_millis: u64,
+    /// The name of the strategy choosing which jobs to run
+    #[serde(default = "default_scheduler_strategy")]
+    pub strategy: String,
+    /// Parameters of the strategy, which depend on the strategy
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            tick_millis: DEFAULT_SCHEDULER_TICK_MILLIS,
+            strategy: default_scheduler_strategy(),
+            params: serde_json::Value::Null,
+        }
+    }
+}
+
+/// How a job is retried when it fails.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryConfig {
+    /// The maximum number of times a job runs, including the first attempt. `1` disables retries.
+    pub max_attempts: u32,
+    /// How long to wait before the first retry, in seconds
+    pub initial_backoff_secs: u64,
+    /// How much the wait grows with every retry
+    pub backoff_multiplier: f64,
+    /// The longest wait between two attempts, in seconds
+    pub max_backoff_secs: u64,
+    /// The kinds of failures that are retried
+    pub retry_on: Vec<FailureKind>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff_secs: 30,
+            backoff_multiplier: 2.0,
+            max_backoff_secs: 60 * 30,
+            retry_on: FailureKind::ALL.to_vec(),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Override this configuration with the fields set in the policy of a job.
+    pub fn with_overrides(&self, policy: &RetryPolicy) -> Result<RetryConfig> {
+        let config = RetryConfig {
+            max_attempts: policy.max_attempts.unwrap_or(self.max_attempts),
+            initial_backoff_secs: policy
+                .initial_backoff_secs
+                .unwrap_or(self.initial_backoff_secs),
+            backoff_multiplier: policy.backoff_multiplier.unwrap_or(self.backoff_multiplier),
+            max_backoff_secs: policy.max_backoff_secs.unwrap_or(self.max_backoff_secs),
+            retry_on: policy
+                .retry_on
+                .clone()
+                .unwrap_or_else(|| self.retry_on.clone()),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Whether a job that failed with the given kind of failure after the given number of attempts should be retried.
+    pub fn should_retry(&self, kind: FailureKind, attempts: u32) -> bool {
+        attempts < self.max_attempts && self.retry_on.contains(&kind)
+    }
+
+    /// How long to wait before retrying a job that failed after the given number of attempts.
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        exponential_backoff(
+            self.initial_backoff_secs,
+            self.backoff_multiplier,
+            self.max_backoff_secs,
+            attempts,
+        )
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.max_attempts == 0 {
+            return Err(Error::msg("maxAttempts must be greater than 0"));
+        }
+
+        validate_backoff(
+            self.initial_backoff_secs,
+            self.backoff_multiplier,
+            self.max_backoff_secs,
+        )
+    }
+}
+
+/// How long to wait before the next attempt after the given number of attempts. The wait starts at `initial_secs`, and is multiplied by
+/// `multiplier` after every attempt up to `max_secs`.
+fn exponential_backoff(
+    initial_secs: u64,
+    multiplier: f64,
+    max_secs: u64,
+    attempts: u32,
+) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(i32::MAX as u32) as i32;
+    let secs = initial_secs as f64 * multiplier.powi(exponent);
+    Duration::from_secs_f64(secs.min(max_secs as f64))
+}
+
+/// Check the parameters of `exponential_backoff`, so waits stay short enough to be represented as a time.
+fn validate_backoff(initial_secs: u64, multiplier: f64, max_secs: u64) -> Result<()> {
+    if multiplier.is_nan() || multiplier < 1.0 {
+        return Err(Error::msg("backoffMultiplier must be at least 1"));
+    }
+
+    if initial_secs > MAX_RETRY_BACKOFF_SECS || max_secs > MAX_RETRY_BACKOFF_SECS {
+        return Err(Error::msg(format!(
+            "initialBackoffSecs and maxBackoffSecs must be at most {}",
+            MAX_RETRY_BACKOFF_SECS
+        )));
+    }
+
+    Ok(())
+}
+
+/// Time limits of jobs. Limits that are not set do not apply.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeoutConfig {
+    /// How long a job can run before it is killed and marked as failed, in seconds
+    pub max_runtime_secs: Option<u64>,
+    /// How long a job can wait in the queue before its first attempt, in seconds
+    pub max_queue_time_secs: Option<u64>,
+}
+
+impl TimeoutConfig {
+    fn validate(&self) -> Result<()> {
+        if self.max_runtime_secs == Some(0) {
+            return Err(Error::msg("maxRuntimeSecs must be greater than 0"));
+        }
+
+        if self.max_queue_time_secs == Some(0) {
+            return Err(Error::msg("maxQueueTimeSecs must be greater than 0"));
+        }
+
+        Ok(())
+    }
+}
+
+/// What to do with jobs that were running when the server stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InterruptedJobPolicy {
+    /// Mark the job as failed, with the interruption as the reason
+    #[default]
+    Fail,
+    /// Queue the job again from the start
+    Requeue,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryConfig {
+    /// What to do with jobs that were running when the server stopped, and did not produce a transcript
+    #[serde(default)]
+    pub interrupted_jobs: InterruptedJobPolicy,
+    /// Whether to remove workspaces that do not belong to any known job and have no transcript to adopt
+    #[serde(default)]
+    pub remove_unknown_workspaces: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownConfig {
+    /// How long to wait for running jobs to finish before stopping them, in seconds
+    #[serde(default)]
+    pub grace_period_secs: u64,
+    /// What to do with jobs that are still running once the grace period is over
+    #[serde(default)]
+    pub interrupted_jobs: InterruptedJobPolicy,
+}
+
+/// The languages and models jobs can ask for.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TranscriptionConfig {
+    /// The language of jobs that do not ask for one, or `auto` to let whisper detect it
+    pub default_language: String,
+    /// The languages jobs can ask for
+    pub languages: Vec<String>,
+    /// The models jobs can ask for
+    pub models: Vec<String>,
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            default_language: String::from("fr"),
+            languages: WHISPER_LANGUAGES.iter().map(|l| l.to_string()).collect(),
+            models: WHISPER_MODELS.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+}
+
+impl TranscriptionConfig {
+    /// The language to run a job with, given the language it asked for. `None` means whisper detects the language.
+    pub fn resolve_language(&self, language: Option<&str>) -> Result<Option<String>> {
+        let language = language.unwrap_or(&self.default_language);
+
+        if language == AUTO_DETECT_LANGUAGE {
+            return Ok(None);
+        }
+
+        if !self.languages.iter().any(|l| l == language) {
+            return Err(Error::msg(format!(
+                "Language \"{}\" is not allowed, expected \"{}\" or one of {:?}",
+                language, AUTO_DETECT_LANGUAGE, self.languages
+            )));
+        }
+
+        Ok(Some(language.to_string()))
+    }
+
+    /// Check that jobs can ask for the given model.
+    pub fn validate_model(&self, model: &str) -> Result<()> {
+        if !self.models.iter().any(|m| m == model) {
+            return Err(Error::msg(format!(
+                "Model \"{}\" is not allowed, expected one of {:?}",
+                model, self.models
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Limits of uploaded files, and how long they are kept.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UploadConfig {
+    /// The largest file that can be uploaded, in megabytes
+    pub max_size_mb: u64,
+    /// The content types of files that can be uploaded, where `audio/*` allows any audio type
+    pub allowed_content_types: Vec<String>,
+    /// How long an uploaded file is kept once its job is finished, in seconds
+    pub retention_secs: u64,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            max_size_mb: 2048,
+            allowed_content_types: vec![String::from("audio/*"), String::from("video/*")],
+            retention_secs: 0,
+        }
+    }
+}
+
+impl UploadConfig {
+    /// The largest file that can be uploaded, in bytes.
+    pub fn max_size_bytes(&self) -> u64 {
+        self.max_size_mb.saturating_mul(1024 * 1024)
+    }
+
+    /// Whether files of the given content type can be uploaded.
+    pub fn allows_content_type(&self, content_type: &str) -> bool {
+        let Some((kind, _)) = content_type.split_once('/') else {
+            return false;
+        };
+
+        self.allowed_content_types.iter().any(|allowed| {
+            allowed.eq_ignore_ascii_case(content_type)
+                || allowed
+                    .strip_suffix("/*")
+                    .is_some_and(|a| a.eq_ignore_ascii_case(kind))
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.max_size_mb == 0 {
+            return Err(Error::msg("maxSizeMb must be greater than 0"));
+        }
+
+        if self.allowed_content_types.is_empty() {
+            return Err(Error::msg("allowedContentTypes must not be empty"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether and how files are prepared before they are transcribed.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PreprocessingConfig {
+    /// Whether files are prepared unless the job asks otherwise
+    pub enabled: bool,
+    /// How files are prepared
+    #[serde(flatten)]
+    pub params: PreprocessingParams,
+}
+
+/// How a file is prepared with ffmpeg before it is transcribed: its audio track is extracted to a mono WAV file, optionally with its
+/// loudness normalized.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PreprocessingParams {
+    /// The ffmpeg program to run
+    pub command: String,
+    /// The sample rate of the WAV file, in Hz
+    pub sample_rate: u32,
+    /// Whether to normalize the loudness of the audio
+    pub normalize_loudness: bool,
+    /// The ffmpeg filter normalizing the loudness
+    pub loudness_filter: String,
 }
 
-pub fn read_config<P: AsRef<Path>>(config_path: P) -> Config {
+impl Default for PreprocessingParams {
+    fn default() -> Self {
+        Self {
+            command: String::from("ffmpeg"),
+            // The sample rate whisper works with
+            sample_rate: 16000,
+            normalize_loudness: true,
+            loudness_filter: String::from("loudnorm=I=-16:TP=-1.5:LRA=11"),
+        }
+    }
+}
+
+impl PreprocessingParams {
+    fn validate(&self) -> Result<()> {
+        if self.sample_rate == 0 {
+            return Err(Error::msg("sampleRate must be greater than 0"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Limits of batches of jobs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BatchConfig {
+    /// The largest number of jobs a batch can have
+    pub max_jobs: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self { max_jobs: 1000 }
+    }
+}
+
+impl BatchConfig {
+    fn validate(&self) -> Result<()> {
+        if self.max_jobs == 0 {
+            return Err(Error::msg("maxJobs must be greater than 0"));
+        }
+
+        Ok(())
+    }
+}
+
+/// How to tell who submits a job. Submitters are told apart by the address of the client unless configured otherwise.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SubmitterConfig {
+    /// A header naming who sends the request, set by a proxy in front of the server. Only safe when clients cannot reach the server
+    /// without going through the proxy, since they could set it themselves otherwise.
+    pub identity_header: Option<String>,
+    /// Whether to trust the `submitter` clients give with their jobs. Any client can then claim to be any submitter.
+    pub trust_clients: bool,
+}
+
+impl SubmitterConfig {
+    fn validate(&self) -> Result<()> {
+        if let Some(header) = &self.identity_header {
+            actix_web::http::header::HeaderName::try_from(header.as_str())
+                .with_context(|| format!("Invalid identityHeader {:?}", header))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Webhooks POSTed the outcome of every job once it finishes, and how deliveries are signed and retried.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebhookConfig {
+    /// The http or https URLs called for every job, besides the callback URL of the job
+    pub urls: Vec<String>,
+    /// The key payloads are signed with using HMAC-SHA256. Payloads are not signed if not set.
+    pub secret: Option<String>,
+    /// The URL clients reach the server at, used in the artifact links of payloads. Defaults to `http://<host>:<port>`.
+    pub public_url: Option<String>,
+    /// The maximum number of times a delivery is attempted, including the first attempt
+    pub max_attempts: u32,
+    /// How long to wait before the first retry, in seconds
+    pub initial_backoff_secs: u64,
+    /// How much the wait grows with every retry
+    pub backoff_multiplier: f64,
+    /// The longest wait between two attempts, in seconds
+    pub max_backoff_secs: u64,
+    /// How long to wait for a webhook to respond, in seconds
+    pub timeout_secs: u64,
+    /// Path of the log every attempt at calling a webhook is appended to. It is kept apart from the workspaces, which are removed when jobs
+    /// are canceled.
+    pub delivery_log_path: String,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            secret: None,
+            public_url: None,
+            max_attempts: 5,
+            initial_backoff_secs: 5,
+            backoff_multiplier: 2.0,
+            max_backoff_secs: 60 * 5,
+            timeout_secs: 10,
+            delivery_log_path: String::from(DEFAULT_WEBHOOK_DELIVERY_LOG_PATH),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// How long to wait before retrying a delivery that failed after the given number of attempts.
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        exponential_backoff(
+            self.initial_backoff_secs,
+            self.backoff_multiplier,
+            self.max_backoff_secs,
+            attempts,
+        )
+    }
+
+    fn validate(&self) -> Result<()> {
+        for url in &self.urls {
+            validate_webhook_url(url).context("Invalid webhook in urls")?;
+        }
+
+        if let Some(url) = &self.public_url {
+            validate_webhook_url(url).context("Invalid publicUrl")?;
+        }
+
+        if self.secret.as_deref() == Some("") {
+            return Err(Error::msg("secret must not be empty"));
+        }
+
+        if self.max_attempts == 0 {
+            return Err(Error::msg("maxAttempts must be greater than 0"));
+        }
+
+        validate_backoff(
+            self.initial_backoff_secs,
+            self.backoff_multiplier,
+            self.max_backoff_secs,
+        )?;
+
+        if self.timeout_secs == 0 {
+            return Err(Error::msg("timeoutSecs must be greater than 0"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Check that the URL can be called as a webhook, i.e. that it is an absolute http or https URL.
+pub fn validate_webhook_url(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).with_context(|| format!("Invalid URL {:?}", url))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(Error::msg(format!("URL {:?} must use http or https", url)));
+    }
+
+    Ok(())
+}
+
+/// The program transcribing files, see `backend::build_backend`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackendConfig {
+    /// The name of the backend
+    pub name: String,
+    /// Parameters of the backend, which depend on the backend
+    pub params: serde_json::Value,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            name: String::from(DEFAULT_BACKEND),
+            params: serde_json::Value::Null,
+        }
+    }
+}
+
+fn default_job_store_path() -> String {
+    String::from(DEFAULT_JOB_STORE_PATH)
+}
+
+fn default_scheduler_tick_millis() -> u64 {
+    DEFAULT_SCHEDULER_TICK_MILLIS
+}
+
+fn default_scheduler_strategy() -> String {
+    String::from(DEFAULT_SCHEDULER_STRATEGY)
+}
+
+pub fn read_config<P: AsRef<Path>>(config_path: P) -> Result<Config> {
     let mut path = PathBuf::new();
     path.push(config_path);
-    let data = std::fs::read_to_string(path.as_path()).unwrap();
-    let config: Config = serde_json::from_str(&data).unwrap();
+    let data = std::fs::read_to_string(path.as_path())
+        .with_context(|| format!("Cannot read config file {:?}", path))?;
+    let config: Config = serde_json::from_str(&data)
+        .with_context(|| format!("Cannot parse config file {:?}", path))?;
 
     let video_storage_path = std::fs::canonicalize(&config.video_storage_path);
 
     if let Ok(v) = video_storage_path {
         if !v.is_dir() {
-            panic!("{} is not a directory", config.video_storage_path);
+            return Err(Error::msg(format!(
+                "{} is not a directory",
+                config.video_storage_path
+            )));
         }
     } else {
-        panic!("Cannot find canonical path for {}", config.video_storage_path);
+        return Err(Error::msg(format!(
+            "Cannot find canonical path for {}",
+            config.video_storage_path
+        )));
     }
 
+    if config.scheduler.tick_millis == 0 {
+        return Err(Error::msg("scheduler.tickMillis must be greater than 0"));
+    }
+
+    config
+        .retry
+        .validate()
+        .context("Invalid retry configuration")?;
+
+    config
+        .timeouts
+        .validate()
+        .context("Invalid timeouts configuration")?;
+
     config
-}
\ No newline at end of file
+        .transcription
+        .resolve_language(None)
+        .context("Invalid transcription.defaultLanguage")?;
+
+    config
+        .uploads
+        .validate()
+        .context("Invalid uploads configuration")?;
+
+    config
+        .preprocessing
+        .params
+        .validate()
+        .context("Invalid preprocessing configuration")?;
+
+    config
+        .webhooks
+        .validate()
+        .context("Invalid webhooks configuration")?;
+
+    config
+        .batches
+        .validate()
+        .context("Invalid batches configuration")?;
+
+    config
+        .submitters
+        .validate()
+        .context("Invalid submitters configuration")?;
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_backoffs_up_to_the_maximum() {
+        let backoffs: Vec<u64> = (1..=5)
+            .map(|attempts| exponential_backoff(5, 2.0, 30, attempts).as_secs())
+            .collect();
+        assert_eq!(backoffs, [5, 10, 20, 30, 30]);
+
+        assert_eq!(
+            exponential_backoff(5, 2.0, 30, u32::MAX),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn bounds_the_backoffs_of_jobs_and_webhooks() {
+        let too_long = MAX_RETRY_BACKOFF_SECS + 1;
+
+        for (initial, max) in [(too_long, 30), (5, too_long), (5, u64::MAX)] {
+            let retry = RetryConfig {
+                initial_backoff_secs: initial,
+                max_backoff_secs: max,
+                ..RetryConfig::default()
+            };
+            assert!(retry.validate().is_err());
+
+            let webhooks = WebhookConfig {
+                initial_backoff_secs: initial,
+                max_backoff_secs: max,
+                ..WebhookConfig::default()
+            };
+            assert!(webhooks.validate().is_err());
+        }
+
+        assert!(RetryConfig::default().validate().is_ok());
+        assert!(WebhookConfig::default().validate().is_ok());
+    }
+}
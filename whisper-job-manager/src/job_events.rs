@@ -0,0 +1,109 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::web::Bytes;
+use futures_util::Stream;
+use tokio::{
+    sync::{
+        broadcast::{self, error::RecvError},
+        Mutex,
+    },
+    time::Interval,
+};
+use uuid::Uuid;
+use whisper_job_manager_models::job_event::JobEvent;
+
+use crate::scheduler::Scheduler;
+
+/// How often to send a comment when no event happens, so proxies do not close the connection
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// State of a stream of job events.
+struct Follow {
+    events: broadcast::Receiver<JobEvent>,
+    /// The job to follow, or none to follow every job
+    uuid: Option<Uuid>,
+    scheduler: Arc<Mutex<Scheduler>>,
+    /// An event to send before receiving more
+    pending: Option<JobEvent>,
+    keep_alive: Interval,
+    done: bool,
+}
+
+impl Follow {
+    /// Whether the stream ends after the given event, which is when the followed job is finished.
+    fn ends_with(&self, event: &JobEvent) -> bool {
+        self.uuid.is_some() && event.status.is_finished()
+    }
+
+    fn send(mut self, event: JobEvent) -> Option<(Result<Bytes, actix_web::Error>, Self)> {
+        self.done = self.ends_with(&event);
+        self.keep_alive.reset();
+        Some((Ok(to_server_sent_event(&event)), self))
+    }
+}
+
+/// Format an event as a server-sent event named after its kind, with the event as JSON data.
+fn to_server_sent_event(event: &JobEvent) -> Bytes {
+    // Serializing the event cannot fail, it only has string keys
+    let data = serde_json::to_string(event).unwrap_or_default();
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event.kind, data))
+}
+
+/// Stream the events of jobs received from the scheduler as server-sent events, starting with `initial` if any. When following a single
+/// job, the events of other jobs are left out and the stream ends once the job is finished.
+pub fn event_stream(
+    events: broadcast::Receiver<JobEvent>,
+    uuid: Option<Uuid>,
+    initial: Option<JobEvent>,
+    scheduler: Arc<Mutex<Scheduler>>,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let follow = Follow {
+        events,
+        uuid,
+        scheduler,
+        pending: initial,
+        keep_alive: tokio::time::interval_at(
+            tokio::time::Instant::now() + KEEP_ALIVE_INTERVAL,
+            KEEP_ALIVE_INTERVAL,
+        ),
+        done: false,
+    };
+
+    futures_util::stream::unfold(follow, |mut follow| async move {
+        if follow.done {
+            return None;
+        }
+
+        if let Some(event) = follow.pending.take() {
+            return follow.send(event);
+        }
+
+        loop {
+            let received = tokio::select! {
+                received = follow.events.recv() => received,
+                _ = follow.keep_alive.tick() => {
+                    return Some((Ok(Bytes::from_static(b": keep-alive\n\n")), follow));
+                }
+            };
+
+            match received {
+                Ok(event) if follow.uuid.is_some_and(|id| id != event.uuid) => continue,
+                Ok(event) => return follow.send(event),
+                Err(RecvError::Lagged(missed)) => {
+                    log::warn!("A client following job events missed {} of them", missed);
+
+                    // The job may have finished in the missed events, so send where it stands now
+                    let Some(id) = follow.uuid else {
+                        continue;
+                    };
+                    let status = follow.scheduler.lock().await.get_job_status(id);
+                    match status {
+                        Some(status) => return follow.send(JobEvent::status_changed(id, status)),
+                        None => return None,
+                    }
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
@@ -0,0 +1,150 @@
+use std::{io::SeekFrom, path::PathBuf, sync::Arc, time::Duration};
+
+use actix_web::web::Bytes;
+use futures_util::Stream;
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt},
+    sync::Mutex,
+};
+use uuid::Uuid;
+use whisper_job_manager_models::job_log::{JobLog, LogStream};
+
+use crate::{
+    scheduler::Scheduler,
+    workspace::{self, STDERR_FILE, STDOUT_FILE},
+};
+
+/// How often to look for new lines when following logs
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The file a single stream of the job with the given UUID is logged to.
+pub fn log_path(uuid: Uuid, stream: LogStream) -> PathBuf {
+    match stream {
+        LogStream::Stderr => workspace::workspace_file(uuid, STDERR_FILE),
+        _ => workspace::workspace_file(uuid, STDOUT_FILE),
+    }
+}
+
+/// Read a single stream of the logs of the job with the given UUID, from the given byte offset or the start, keeping only the given number of
+/// lines at the end if any.
+pub async fn read_log(
+    uuid: Uuid,
+    stream: LogStream,
+    offset: Option<u64>,
+    tail: Option<usize>,
+) -> std::io::Result<JobLog> {
+    let bytes = tokio::fs::read(log_path(uuid, stream)).await?;
+    let end = bytes.len();
+
+    let mut start = offset.map_or(0, |o| (o as usize).min(end));
+    if let Some(tail) = tail {
+        start = start.max(tail_start(&bytes, tail));
+    }
+
+    Ok(JobLog {
+        stream,
+        content: String::from_utf8_lossy(&bytes[start..end]).into_owned(),
+        offset: start as u64,
+        next_offset: end as u64,
+    })
+}
+
+/// Where the last `lines` lines of the log start, ignoring the line break at the very end.
+fn tail_start(bytes: &[u8], lines: usize) -> usize {
+    if lines == 0 {
+        return bytes.len();
+    }
+
+    let content = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    content
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, b)| **b == b'\n')
+        .nth(lines - 1)
+        .map_or(0, |(idx, _)| idx + 1)
+}
+
+/// State of logs being followed.
+struct Follow {
+    uuid: Uuid,
+    scheduler: Arc<Mutex<Scheduler>>,
+    /// The streams being followed, with the offset to read from next
+    streams: Vec<(LogStream, u64)>,
+    /// Whether everything was sent
+    done: bool,
+}
+
+impl Follow {
+    /// Read the complete lines logged since the last read, or everything left if the job is finished.
+    async fn read_new_lines(&mut self, finished: bool) -> Vec<u8> {
+        let mut chunk = vec![];
+
+        for (stream, offset) in self.streams.iter_mut() {
+            let path = log_path(self.uuid, *stream);
+            let mut bytes = vec![];
+            let read = async {
+                let mut file = tokio::fs::File::open(path.as_path()).await?;
+                file.seek(SeekFrom::Start(*offset)).await?;
+                file.read_to_end(&mut bytes).await
+            };
+            if let Err(e) = read.await {
+                log::warn!("Could not follow log {:?}: {}", path, e);
+                continue;
+            }
+
+            // Lines of both streams are interleaved, so only whole lines are sent until the end
+            let len = if finished {
+                bytes.len()
+            } else {
+                bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1)
+            };
+
+            chunk.extend_from_slice(&bytes[..len]);
+            *offset += len as u64;
+        }
+
+        chunk
+    }
+}
+
+/// Stream the logs of the job with the given UUID from the given offsets, sending new lines as they are logged until the job is finished.
+pub fn follow_logs(
+    uuid: Uuid,
+    streams: Vec<(LogStream, u64)>,
+    scheduler: Arc<Mutex<Scheduler>>,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let follow = Follow {
+        uuid,
+        scheduler,
+        streams,
+        done: false,
+    };
+
+    futures_util::stream::unfold(follow, |mut follow| async move {
+        loop {
+            if follow.done {
+                return None;
+            }
+
+            // Check before reading, so nothing logged after the last read is missed
+            let finished = follow
+                .scheduler
+                .lock()
+                .await
+                .get_job_status(follow.uuid)
+                .is_none_or(|s| s.is_finished());
+
+            let chunk = follow.read_new_lines(finished).await;
+            follow.done = finished;
+
+            if !chunk.is_empty() {
+                return Some((Ok(Bytes::from(chunk)), follow));
+            }
+
+            if !finished {
+                tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+            }
+        }
+    })
+}
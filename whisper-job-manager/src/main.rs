@@ -1,25 +1,40 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{path::PathBuf, sync::Arc};
 
 use actix_web::{middleware, web, App, HttpServer};
-use tokio::sync::Mutex;
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::{mpsc, Mutex},
+};
 
 use crate::{
+    backend::TranscriptionBackend,
     constants::TMP_DIR,
     routes::{
-        cancel_job::cancel_job, get_all_statuses::get_all_statuses, get_job::get_job,
-        get_status::get_status, new_job::new_job,
+        cancel_batch::cancel_batch, cancel_job::cancel_job, get_all_statuses::get_all_statuses,
+        get_artifacts::get_artifacts, get_batch::get_batch,
+        get_batch_artifacts::get_batch_artifacts, get_job::get_job, get_job_events::get_job_events,
+        get_job_logs::get_job_logs, get_status::get_status,
+        get_webhook_deliveries::get_webhook_deliveries, new_batch::new_batch, new_job::new_job,
+        upload_job::upload_job, v2,
     },
 };
 
+mod backend;
 mod config;
 mod constants;
+mod job_events;
+mod logs;
+mod media;
+mod recovery;
 mod routes;
 mod scheduler;
+mod shutdown;
+mod store;
+mod subtitles;
+mod webhooks;
+mod workspace;
 
-const DEFAULT_CONFIG_FILE: &'static str = "config.json";
-
-// TODO should be config
-const SCHEDULER_RUN_PERIOD_MILLIS: u64 = 1000 * 30;
+const DEFAULT_CONFIG_FILE: &str = "config.json";
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -40,43 +55,139 @@ async fn main() -> std::io::Result<()> {
         config_path.push(&args[1])
     }
 
-    let config = config::read_config(config_path);
+    let config = config::read_config(config_path.as_path()).map_err(|e| {
+        log::error!("Invalid config: {:#}", e);
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{:#}", e))
+    })?;
 
     log::info!("Loaded config: {:?}", config);
 
     log::info!("Creating temporary file directory {:?}", TMP_DIR.as_path());
     std::fs::create_dir_all(TMP_DIR.as_path())?;
 
-    let scheduler_instance = Arc::new(Mutex::new(scheduler::Scheduler::default()));
+    log::info!("Opening job store {}", config.job_store_path);
+    let (mut job_store, job_records) = store::JobStore::open(&config.job_store_path)
+        .map_err(|e| std::io::Error::other(format!("Could not open job store: {}", e)))?;
+
+    let backend: Arc<dyn TranscriptionBackend> = backend::build_backend(&config.backend)
+        .map_err(|e| {
+            log::error!("Invalid backend configuration: {:#}", e);
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{:#}", e))
+        })?
+        .into();
+    log::info!("Using transcription backend {:?}", backend);
+
+    log::info!("Recovering jobs from {:?}", TMP_DIR.as_path());
+    let (job_records, recovery_report) = recovery::recover_jobs(
+        &mut job_store,
+        job_records,
+        &config.recovery,
+        backend.as_ref(),
+    )
+    .await
+    .map_err(|e| std::io::Error::other(format!("Could not recover jobs: {}", e)))?;
+    log::info!("Recovery finished: {:?}", recovery_report);
+
+    let job_store = job_store
+        .spawn_writer()
+        .map_err(|e| std::io::Error::other(format!("Could not start job store writer: {}", e)))?;
+
+    let strategy = scheduler::strategy::build_strategy(&config.scheduler).map_err(|e| {
+        log::error!("Invalid scheduler configuration: {:#}", e);
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{:#}", e))
+    })?;
+    log::info!("Using scheduler strategy {:?}", strategy);
+
+    let (events_tx, events_rx) = mpsc::unbounded_channel();
+    let (finished_jobs_tx, finished_jobs_rx) = mpsc::unbounded_channel();
+    let scheduler_instance = Arc::new(Mutex::new(scheduler::Scheduler::new(
+        job_store,
+        job_records,
+        strategy,
+        backend.clone(),
+        events_tx.clone(),
+        finished_jobs_tx,
+    )));
     let config = Arc::new(config);
     let config_data = web::Data::new(config.clone());
     let app_state = web::Data::new(scheduler_instance.clone());
+    let backend_data = web::Data::new(backend.clone());
 
     log::info!("Starting scheduler task...");
 
-    let scheduler_instance_background_task = scheduler_instance.clone();
+    actix_web::rt::spawn(scheduler::events::run_event_loop(
+        scheduler_instance.clone(),
+        events_rx,
+        config.scheduler.clone(),
+    ));
+
+    log::info!("Starting webhook dispatcher...");
+
+    actix_web::rt::spawn(webhooks::run_dispatcher(
+        finished_jobs_rx,
+        config.clone(),
+        backend,
+    ));
+
+    // Reload the scheduler configuration on SIGHUP
+    let mut hangup = signal(SignalKind::hangup())?;
     actix_web::rt::spawn(async move {
-        loop {
-            actix_web::rt::time::sleep(Duration::from_millis(SCHEDULER_RUN_PERIOD_MILLIS)).await;
-            let mut sch = scheduler_instance_background_task.lock().await;
-            sch.run().await;
+        while hangup.recv().await.is_some() {
+            log::info!("Received SIGHUP, reloading config {:?}", config_path);
+            match config::read_config(config_path.as_path()) {
+                Ok(c) => {
+                    let event = scheduler::events::SchedulerEvent::ConfigChanged(c.scheduler);
+                    if let Err(e) = events_tx.send(event) {
+                        log::error!("Could not send reloaded config to scheduler: {}", e);
+                    }
+                }
+                Err(e) => log::error!("Invalid config, keeping the current one: {:#}", e),
+            }
         }
     });
 
+    // Signals are handled here rather than by actix, so running jobs are stopped and recorded before the server stops
+    let interrupt = signal(SignalKind::interrupt())?;
+    let terminate = signal(SignalKind::terminate())?;
+
     log::info!("Starting server at {}:{}", config.host, config.port);
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
             .wrap(middleware::Logger::default())
             .app_data(app_state.clone())
             .app_data(config_data.clone())
+            .app_data(backend_data.clone())
+            .app_data(routes::error::json_config())
+            .app_data(routes::error::query_config())
+            .app_data(routes::error::path_config())
             .service(new_job)
+            .service(upload_job)
             .service(cancel_job)
             .service(get_status)
             .service(get_job)
             .service(get_all_statuses)
+            .service(get_artifacts)
+            .service(get_job_logs)
+            .service(get_job_events)
+            .service(get_webhook_deliveries)
+            .service(new_batch)
+            .service(get_batch)
+            .service(cancel_batch)
+            .service(get_batch_artifacts)
+            .service(v2::scope())
     })
+    .disable_signals()
     .bind((config.host.clone(), config.port))?
-    .run()
-    .await
+    .run();
+
+    actix_web::rt::spawn(shutdown::shutdown_on_signal(
+        interrupt,
+        terminate,
+        server.handle(),
+        scheduler_instance,
+        config.shutdown.clone(),
+    ));
+
+    server.await
 }
@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::config::PreprocessingParams;
+
+/// Find the duration of an audio or video file with `ffprobe`, in seconds. Errors are logged, and give no duration.
+pub async fn probe_duration(path: &Path) -> Option<f64> {
+    let output = Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-show_entries")
+        .arg("format=duration")
+        .arg("-of")
+        .arg("default=noprint_wrappers=1:nokey=1")
+        .arg(path)
+        .output()
+        .await;
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        Ok(o) => {
+            log::warn!(
+                "ffprobe could not read the duration of {:?}: {}",
+                path,
+                String::from_utf8_lossy(&o.stderr).trim()
+            );
+            return None;
+        }
+        Err(e) => {
+            log::warn!("Could not run ffprobe on {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    let duration = String::from_utf8_lossy(&output.stdout);
+    match duration.trim().parse::<f64>() {
+        Ok(d) => Some(d),
+        Err(e) => {
+            log::warn!(
+                "Invalid duration {:?} from ffprobe for {:?}: {}",
+                duration.trim(),
+                path,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Build the ffmpeg command writing the audio track of `source` to the WAV file at `output`, keeping only the part between
+/// `start_secs` and `end_secs` if they are set.
+pub fn preprocess_command(
+    source: &Path,
+    output: &Path,
+    params: &PreprocessingParams,
+    start_secs: Option<f64>,
+    end_secs: Option<f64>,
+) -> Command {
+    let mut cmd = Command::new(&params.command);
+    cmd.arg("-hide_banner")
+        .arg("-nostdin")
+        .arg("-nostats")
+        .arg("-loglevel")
+        .arg("warning")
+        .arg("-y");
+
+    // Seeking before the input is fast, and makes timestamps of the output start at 0
+    if let Some(start) = start_secs {
+        cmd.arg("-ss").arg(start.to_string());
+    }
+
+    cmd.arg("-i").arg(source);
+
+    if let Some(end) = end_secs {
+        let duration = end - start_secs.unwrap_or(0.0);
+        cmd.arg("-t").arg(duration.to_string());
+    }
+
+    cmd.arg("-vn")
+        .arg("-ac")
+        .arg("1")
+        .arg("-ar")
+        .arg(params.sample_rate.to_string());
+
+    if params.normalize_loudness {
+        cmd.arg("-af").arg(&params.loudness_filter);
+    }
+
+    cmd.arg("-c:a").arg("pcm_s16le").arg(output);
+
+    cmd
+}
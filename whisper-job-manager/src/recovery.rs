@@ -0,0 +1,179 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use whisper_job_manager_models::{job_metadata::JobMetadata, job_status::JobStatus};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::{InterruptedJobPolicy, RecoveryConfig},
+    constants::TMP_DIR,
+    scheduler::job_spec::JobSpec,
+    store::{JobRecord, JobStore},
+    workspace,
+};
+
+/// Summary of what was done to reconcile the job store with the workspaces on disk.
+#[derive(Debug, Default)]
+pub struct RecoveryReport {
+    /// Interrupted jobs whose workspace had a transcript, and were marked as succeeded
+    pub completed: Vec<Uuid>,
+    /// Interrupted jobs that were marked as failed
+    pub failed: Vec<Uuid>,
+    /// Interrupted jobs that were queued again
+    pub requeued: Vec<Uuid>,
+    /// Workspaces unknown to the job store that had a transcript, and were added as succeeded jobs
+    pub adopted: Vec<Uuid>,
+    /// Entries in the temporary directory that do not belong to any job
+    pub unknown: Vec<PathBuf>,
+    /// Unknown entries that were removed
+    pub removed: Vec<PathBuf>,
+}
+
+/// Reconcile the records loaded from the job store with the workspaces in `TMP_DIR`, after the server stopped without
+/// recording the end of its running jobs. Any record changed or added is written to the store, and the reconciled
+/// records are returned.
+pub async fn recover_jobs(
+    store: &mut JobStore,
+    records: Vec<JobRecord>,
+    config: &RecoveryConfig,
+    backend: &dyn TranscriptionBackend,
+) -> Result<(Vec<JobRecord>, RecoveryReport)> {
+    let mut report = RecoveryReport::default();
+    let known_ids: HashSet<Uuid> = records.iter().map(|r| r.id).collect();
+    let mut records_by_id: HashMap<Uuid, JobRecord> =
+        records.into_iter().map(|r| (r.id, r)).collect();
+
+    // Resolve jobs that were running when the server stopped
+    for record in records_by_id.values_mut() {
+        if !matches!(record.status, JobStatus::Running | JobStatus::Preprocessing) {
+            continue;
+        }
+
+        // The outputs of a job transcribing part of its source may have been written but not shifted yet, so they cannot be trusted
+        let shifted = record.spec.output_offset_secs().is_some();
+        if shifted {
+            remove_outputs(record.id, &record.spec, backend);
+        }
+
+        let transcribed = !shifted
+            && record
+                .spec
+                .output_formats
+                .iter()
+                .any(|format| backend.find_output(record.id, *format).is_some());
+
+        if transcribed {
+            // Backends only write the transcript at the very end, so the job finished before its exit was recorded
+            record.status = JobStatus::Succeeded;
+            report.completed.push(record.id);
+        } else {
+            match config.interrupted_jobs {
+                InterruptedJobPolicy::Fail => {
+                    record.status = JobStatus::Failed {
+                        reason: Some(String::from("Server stopped while the job was running")),
+                    };
+                    report.failed.push(record.id);
+                }
+                InterruptedJobPolicy::Requeue => {
+                    // The logs of the interrupted attempt are kept, and the next attempt is appended to them
+                    tokio::fs::create_dir_all(workspace::workspace_path(record.id)).await?;
+                    record.status = JobStatus::Queued;
+                    report.requeued.push(record.id);
+                }
+            }
+        }
+
+        record.metadata.updated_at = chrono::offset::Utc::now();
+        store.append(record)?;
+    }
+
+    // Look for workspaces the job store does not know about
+    for entry in std::fs::read_dir(TMP_DIR.as_path())? {
+        let path = entry?.path();
+
+        let id = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| Uuid::parse_str(n).ok());
+
+        match id {
+            Some(id) if known_ids.contains(&id) => continue,
+            Some(id) if path.is_dir() => {
+                if let Some(record) = adopt_workspace(id, path.as_path()) {
+                    store.append(&record)?;
+                    records_by_id.insert(id, record);
+                    report.adopted.push(id);
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        log::warn!("{:?} does not belong to any known job", path);
+
+        if config.remove_unknown_workspaces {
+            let removed = if path.is_dir() {
+                std::fs::remove_dir_all(path.as_path())
+            } else {
+                std::fs::remove_file(path.as_path())
+            };
+
+            match removed {
+                Ok(()) => report.removed.push(path.clone()),
+                Err(e) => log::error!("Failed to remove {:?}: {}", path, e),
+            }
+        }
+
+        report.unknown.push(path);
+    }
+
+    let mut records: Vec<JobRecord> = records_by_id.into_values().collect();
+    records.sort_by_key(|r| r.metadata.created_at);
+
+    Ok((records, report))
+}
+
+/// Remove the outputs a job left in its workspace, e.g. because it was interrupted before they were complete.
+pub fn remove_outputs(id: Uuid, spec: &JobSpec, backend: &dyn TranscriptionBackend) {
+    for format in spec.output_formats.iter() {
+        if let Some(path) = backend.find_output(id, *format) {
+            match std::fs::remove_file(path.as_path()) {
+                Ok(()) => log::info!("Removed output {:?} of job {}", path, id),
+                Err(e) => log::error!("Failed to remove {:?}: {}", path, e),
+            }
+        }
+    }
+}
+
+/// Create a succeeded job for a workspace that has a transcript but no record, so the transcript can be downloaded again.
+fn adopt_workspace(id: Uuid, path: &Path) -> Option<JobRecord> {
+    let transcript = workspace::find_file_with_extension(path, "srt")?;
+
+    // The transcript is named after the transcribed file, which is the best guess left of the original filename
+    let filename = PathBuf::from(transcript.file_stem()?);
+    let created_at: DateTime<Utc> = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(DateTime::from)
+        .unwrap_or_else(|_| chrono::offset::Utc::now());
+
+    log::info!(
+        "Adopting workspace {:?} with transcript {:?}",
+        path,
+        transcript
+    );
+
+    let mut metadata = JobMetadata::init_for_queued_job(filename.clone());
+    metadata.created_at = created_at;
+
+    Some(JobRecord {
+        id,
+        spec: JobSpec::new(filename),
+        status: JobStatus::Succeeded,
+        metadata,
+    })
+}
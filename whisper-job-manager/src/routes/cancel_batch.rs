@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, post, web, HttpResponse, Responder};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::batch::{CancelBatchRequest, CancelBatchResponse};
+
+use crate::{
+    routes::error::{batch_not_found, error_response},
+    scheduler::Scheduler,
+    workspace,
+};
+
+/// Cancel the jobs of a batch that are not finished yet and remove their workspaces, like `/cancelJob` does. Returns the jobs that were
+/// canceled, or `None` if no job belongs to the batch.
+pub async fn cancel_batch_jobs(sch: &Mutex<Scheduler>, batch: Uuid) -> Option<Vec<Uuid>> {
+    let canceled = {
+        let mut sch = sch.lock().await;
+        if sch.get_batch_jobs(batch).is_empty() {
+            return None;
+        }
+        sch.cancel_batch(batch).await
+    };
+
+    for id in canceled.iter().copied() {
+        workspace::cleanup_workspace(workspace::workspace_path(id)).await;
+    }
+
+    log::info!("Canceled {} jobs of batch {}", canceled.len(), batch);
+
+    Some(canceled)
+}
+
+/// Request handler for canceling the jobs of a batch.
+#[post("/cancelBatch")]
+pub async fn cancel_batch(
+    json: web::Json<CancelBatchRequest>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match cancel_batch_jobs(&sch, json.uuid).await {
+        Some(canceled) => HttpResponse::Ok().json(CancelBatchResponse { canceled }),
+        None => error_response(StatusCode::BAD_REQUEST, batch_not_found(json.uuid)),
+    }
+}
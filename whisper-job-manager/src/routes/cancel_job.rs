@@ -1,10 +1,10 @@
 use std::sync::Arc;
 
-use actix_web::{post, web, HttpResponse, Responder};
+use actix_web::{http::StatusCode, post, web, HttpResponse, Responder};
 use tokio::sync::Mutex;
-use whisper_job_manager_models::CancelJobRequest;
+use whisper_job_manager_models::{error::ErrorResponse, CancelJobRequest};
 
-use crate::{constants::TMP_DIR, scheduler::Scheduler};
+use crate::{routes::error::error_response, scheduler::Scheduler, workspace};
 
 /// Request handler for canceling a job.
 #[post("/cancelJob")]
@@ -15,14 +15,15 @@ pub async fn cancel_job(
     let uuid = json.uuid;
 
     if let Err(e) = sch.lock().await.cancel_job(uuid).await {
-        log::error!("Failed to cancel job with ID {}: {}", uuid, e);
-        // TODO need to split up error type into user or server
-        return HttpResponse::InternalServerError();
+        let status = if e.is_client_error() {
+            StatusCode::BAD_REQUEST
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        };
+        return error_response(status, ErrorResponse::new(e.code(), e.to_string()));
     }
 
-    let mut workspace = TMP_DIR.clone();
-    workspace.push(uuid.to_string());
-    super::cleanup_workspace(workspace).await;
+    workspace::cleanup_workspace(workspace::workspace_path(uuid)).await;
 
-    HttpResponse::Ok()
+    HttpResponse::Ok().finish()
 }
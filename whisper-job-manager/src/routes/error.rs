@@ -0,0 +1,67 @@
+use actix_web::{error::InternalError, http::StatusCode, web, HttpResponse};
+use uuid::Uuid;
+use whisper_job_manager_models::error::{ErrorCode, ErrorResponse};
+
+/// Respond with the given status and the error as body, logging the error.
+pub fn error_response(status: StatusCode, error: ErrorResponse) -> HttpResponse {
+    if status.is_server_error() {
+        log::error!("{}", error);
+    } else {
+        log::warn!("{}", error);
+    }
+
+    HttpResponse::build(status).json(error)
+}
+
+/// The error for a job that cannot be found.
+pub fn job_not_found(id: Uuid) -> ErrorResponse {
+    ErrorResponse::new(
+        ErrorCode::JobNotFound,
+        format!("Job {} could not be found", id),
+    )
+}
+
+/// The error for a batch no job belongs to.
+pub fn batch_not_found(id: Uuid) -> ErrorResponse {
+    ErrorResponse::new(
+        ErrorCode::BatchNotFound,
+        format!("Batch {} could not be found", id),
+    )
+}
+
+/// The error for a job that is still queued or running.
+pub fn job_not_finished(id: Uuid) -> ErrorResponse {
+    ErrorResponse::new(
+        ErrorCode::JobNotFinished,
+        format!("Job {} is not finished", id),
+    )
+}
+
+/// Turn an error of an extractor into a response, so malformed requests get an error body like any other failure.
+fn invalid_request(
+    err: impl std::fmt::Display + std::fmt::Debug + 'static,
+    status: StatusCode,
+) -> actix_web::Error {
+    let response = error_response(
+        status,
+        ErrorResponse::new(ErrorCode::InvalidRequest, err.to_string()),
+    );
+    InternalError::from_response(err, response).into()
+}
+
+/// Parse JSON bodies, answering with an error body when they are malformed.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(|err, _| invalid_request(err, StatusCode::BAD_REQUEST))
+}
+
+/// Parse query strings, answering with an error body when they are malformed.
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default()
+        .error_handler(|err, _| invalid_request(err, StatusCode::BAD_REQUEST))
+}
+
+/// Parse path segments, answering with an error body when they are malformed. A malformed segment, e.g. an invalid UUID, cannot name an
+/// existing resource, hence the `404`.
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(|err, _| invalid_request(err, StatusCode::NOT_FOUND))
+}
@@ -1,30 +1,282 @@
 use std::sync::Arc;
 
-use actix_web::{get, web, HttpResponse, Responder};
+use actix_web::{get, http::StatusCode, web, HttpResponse, Responder};
+use anyhow::{Context, Error, Result};
 use tokio::sync::Mutex;
-use whisper_job_manager_models::{GetAllStatusesResponse, GetStatusResponse};
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    error::{ErrorCode, ErrorResponse},
+    job_query::{split_list, ListJobsRequest, SortField, SortOrder},
+    job_status::JobStatus,
+    GetAllStatusesResponse, GetStatusResponse,
+};
 
-use crate::scheduler::Scheduler;
+use crate::{routes::error::error_response, scheduler::Scheduler};
 
+use super::get_status::status_response;
+
+/// A page of jobs matching a query.
+pub struct JobPage {
+    /// The jobs of the page, in the order asked for
+    pub jobs: Vec<GetStatusResponse>,
+    /// The cursor to pass to get the next page, if there are more jobs
+    pub next_cursor: Option<String>,
+}
+
+/// Where a page starts: right after the job with the given sort key and UUID. Jobs are sorted by their UUID after their sort key, so the
+/// position of a job does not change between pages unless it is updated.
+struct Cursor {
+    /// The sort key of the last job of the previous page, in microseconds since the epoch
+    key: i64,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn parse(cursor: &str) -> Result<Self> {
+        let (key, id) = cursor
+            .split_once('_')
+            .ok_or_else(|| Error::msg("Malformed cursor"))?;
+
+        Ok(Cursor {
+            key: key.parse().context("Malformed cursor")?,
+            id: Uuid::parse_str(id).context("Malformed cursor")?,
+        })
+    }
+
+    fn encode(key: i64, id: Uuid) -> String {
+        format!("{}_{}", key, id)
+    }
+}
+
+/// The sort key of a job, in microseconds since the epoch.
+fn sort_key(job: &GetStatusResponse, field: SortField) -> i64 {
+    match field {
+        SortField::CreatedAt => job.metadata.created_at.timestamp_micros(),
+        SortField::UpdatedAt => job.metadata.updated_at.timestamp_micros(),
+    }
+}
+
+/// Whether a job matches the filters of the query.
+fn matches(
+    job: &GetStatusResponse,
+    query: &ListJobsRequest,
+    statuses: &[&str],
+    tags: &[&str],
+) -> bool {
+    let metadata = &job.metadata;
+
+    if !statuses.is_empty() && !statuses.contains(&job.status.name()) {
+        return false;
+    }
+
+    if let Some(filename) = &query.filename {
+        let job_filename = metadata.filename.to_string_lossy().to_lowercase();
+        if !job_filename.contains(&filename.to_lowercase()) {
+            return false;
+        }
+    }
+
+    if query.created_after.is_some_and(|t| metadata.created_at < t)
+        || query
+            .created_before
+            .is_some_and(|t| metadata.created_at >= t)
+        || query.updated_after.is_some_and(|t| metadata.updated_at < t)
+        || query
+            .updated_before
+            .is_some_and(|t| metadata.updated_at >= t)
+    {
+        return false;
+    }
+
+    if query.batch.is_some() && metadata.batch_id != query.batch {
+        return false;
+    }
+
+    if let Some(language) = &query.language {
+        let matches_language = [&metadata.language, &metadata.detected_language]
+            .into_iter()
+            .flatten()
+            .any(|l| l.eq_ignore_ascii_case(language));
+        if !matches_language {
+            return false;
+        }
+    }
+
+    tags.iter()
+        .all(|tag| metadata.tags.iter().any(|t| t == tag))
+}
+
+/// List the jobs matching the query, sorted and paginated as it asks. The page has at most `default_limit` jobs when the query has no
+/// limit, or every job when there is no default either.
+pub fn list_jobs(
+    sch: &Scheduler,
+    query: &ListJobsRequest,
+    default_limit: Option<usize>,
+) -> Result<JobPage> {
+    let statuses: Vec<&str> = query
+        .status
+        .as_deref()
+        .map(split_list)
+        .into_iter()
+        .flatten()
+        .collect();
+    if let Some(unknown) = statuses.iter().find(|s| !JobStatus::NAMES.contains(s)) {
+        return Err(Error::msg(format!(
+            "Unknown status {:?}, expected one of {}",
+            unknown,
+            JobStatus::NAMES.join(", ")
+        )));
+    }
+
+    let tags: Vec<&str> = query
+        .tags
+        .as_deref()
+        .map(split_list)
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let limit = query.limit.or(default_limit);
+    if limit == Some(0) {
+        return Err(Error::msg("limit must be greater than 0"));
+    }
+
+    let cursor = query.cursor.as_deref().map(Cursor::parse).transpose()?;
+
+    let mut jobs: Vec<(i64, Uuid, GetStatusResponse)> = sch
+        .get_all_job_statuses()
+        .keys()
+        .filter_map(|id| status_response(sch, *id))
+        .filter(|job| matches(job, query, &statuses, &tags))
+        .map(|job| (sort_key(&job, query.sort), job.uuid, job))
+        .collect();
+
+    let next_cursor = paginate(&mut jobs, query.order, cursor, limit);
+
+    Ok(JobPage {
+        jobs: jobs.into_iter().map(|(_, _, job)| job).collect(),
+        next_cursor,
+    })
+}
+
+/// Sort the jobs by their sort key and UUID in the given order, and only keep the page starting after the cursor. Returns the cursor of the
+/// next page, if there are more jobs than the limit.
+fn paginate<T>(
+    jobs: &mut Vec<(i64, Uuid, T)>,
+    order: SortOrder,
+    cursor: Option<Cursor>,
+    limit: Option<usize>,
+) -> Option<String> {
+    jobs.sort_by_key(|(key, id, _)| (*key, *id));
+    if order == SortOrder::Desc {
+        jobs.reverse();
+    }
+
+    if let Some(cursor) = cursor {
+        let after_cursor = |key: i64, id: Uuid| match order {
+            SortOrder::Asc => (key, id) > (cursor.key, cursor.id),
+            SortOrder::Desc => (key, id) < (cursor.key, cursor.id),
+        };
+        jobs.retain(|(key, id, _)| after_cursor(*key, *id));
+    }
+
+    match limit {
+        Some(limit) if jobs.len() > limit => {
+            jobs.truncate(limit);
+            jobs.last().map(|(key, id, _)| Cursor::encode(*key, *id))
+        }
+        _ => None,
+    }
+}
+
+/// Request handler for listing jobs. Without a query, every job is listed.
 #[get("/getAllStatuses")]
-pub async fn get_all_statuses(sch: web::Data<Arc<Mutex<Scheduler>>>) -> impl Responder {
-    let sch_guard = sch.lock().await;
-
-    let status_map = sch_guard.get_all_job_statuses();
-    let mut statuses = Vec::with_capacity(status_map.len());
-
-    for id in status_map.keys() {
-        let status = status_map.get(id).unwrap().clone();
-        let metadata = sch_guard.get_job_metadata(*id);
-        if metadata.is_none() {
-            log::warn!(
-                "Could not find associated metadata for job with ID {}, skipping",
-                id
+pub async fn get_all_statuses(
+    query: web::Query<ListJobsRequest>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match list_jobs(&*sch.lock().await, &query, None) {
+        Ok(page) => HttpResponse::Ok().json(GetAllStatusesResponse {
+            statuses: page.jobs,
+            next_cursor: page.next_cursor,
+        }),
+        Err(e) => error_response(
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new(ErrorCode::InvalidRequest, format!("{:#}", e)),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Jobs with the given sort keys, and UUIDs going up in the order of the keys.
+    fn jobs(keys: &[i64]) -> Vec<(i64, Uuid, usize)> {
+        keys.iter()
+            .enumerate()
+            .map(|(idx, key)| (*key, Uuid::from_u128(idx as u128), idx))
+            .collect()
+    }
+
+    /// Walk every page of the jobs, and return the jobs in the order they were listed.
+    fn walk(keys: &[i64], order: SortOrder, limit: usize) -> Vec<usize> {
+        let mut listed = vec![];
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut page = jobs(keys);
+            let next_cursor = paginate(
+                &mut page,
+                order,
+                cursor.as_deref().map(|c| Cursor::parse(c).unwrap()),
+                Some(limit),
             );
+            assert!(page.len() <= limit);
+            listed.extend(page.into_iter().map(|(_, _, idx)| idx));
+
+            match next_cursor {
+                Some(c) => cursor = Some(c),
+                None => return listed,
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_cursors() {
+        let id = Uuid::new_v4();
+        let cursor = Cursor::parse(&Cursor::encode(-1_700_000_000_123_456, id)).unwrap();
+        assert_eq!(cursor.key, -1_700_000_000_123_456);
+        assert_eq!(cursor.id, id);
+    }
+
+    #[test]
+    fn rejects_malformed_cursors() {
+        for cursor in [
+            "",
+            "123",
+            "abc_00000000-0000-0000-0000-000000000000",
+            "123_abc",
+        ] {
+            assert!(Cursor::parse(cursor).is_err());
         }
-        let metadata = metadata.unwrap();
-        statuses.push(GetStatusResponse { status, metadata })
     }
 
-    HttpResponse::Ok().json(GetAllStatusesResponse { statuses })
+    #[test]
+    fn lists_every_job_once_across_pages() {
+        // Jobs sharing a sort key are told apart by their UUID
+        let keys = [30, 10, 20, 10, 40, 20, 10];
+        assert_eq!(walk(&keys, SortOrder::Asc, 2), [1, 3, 6, 2, 5, 0, 4]);
+        assert_eq!(walk(&keys, SortOrder::Desc, 3), [4, 0, 5, 2, 6, 3, 1]);
+    }
+
+    #[test]
+    fn has_no_next_page_without_more_jobs() {
+        let mut page = jobs(&[1, 2]);
+        assert_eq!(paginate(&mut page, SortOrder::Asc, None, Some(2)), None);
+        assert_eq!(page.len(), 2);
+
+        let mut page = jobs(&[1, 2]);
+        assert_eq!(paginate(&mut page, SortOrder::Asc, None, None), None);
+    }
 }
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use actix_web::{get, http::StatusCode, web, HttpResponse, Responder};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    output_format::OutputFormat, Artifact, GetArtifactsRequest, GetArtifactsResponse,
+};
+
+use crate::{
+    backend::TranscriptionBackend,
+    routes::error::{error_response, job_not_finished, job_not_found},
+    scheduler::Scheduler,
+};
+
+/// The files of the given formats a job produced.
+pub fn list_artifacts(
+    backend: &dyn TranscriptionBackend,
+    id: Uuid,
+    output_formats: Vec<OutputFormat>,
+) -> Vec<Artifact> {
+    output_formats
+        .into_iter()
+        .filter_map(|format| {
+            let path = backend.find_output(id, format)?;
+            let size = std::fs::metadata(path.as_path()).ok()?.len();
+            let filename = path.file_name()?.to_string_lossy().into_owned();
+            Some(Artifact {
+                format,
+                filename,
+                size,
+            })
+        })
+        .collect()
+}
+
+/// Request handler for listing the files a finished job produced.
+#[get("/getArtifacts")]
+pub async fn get_artifacts(
+    query: web::Query<GetArtifactsRequest>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = query.uuid;
+
+    let sch = sch.lock().await;
+
+    let Some(status) = sch.get_job_status(id) else {
+        return error_response(StatusCode::BAD_REQUEST, job_not_found(id));
+    };
+
+    if !status.is_finished() {
+        return error_response(StatusCode::BAD_REQUEST, job_not_finished(id));
+    }
+
+    let Some(metadata) = sch.get_job_metadata(id) else {
+        return error_response(StatusCode::BAD_REQUEST, job_not_found(id));
+    };
+    let output_formats = metadata.output_formats;
+
+    let artifacts = list_artifacts(backend.as_ref().as_ref(), id, output_formats);
+
+    HttpResponse::Ok().json(GetArtifactsResponse { artifacts })
+}
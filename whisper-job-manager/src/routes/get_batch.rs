@@ -0,0 +1,74 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use actix_web::{get, http::StatusCode, web, HttpResponse, Responder};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    batch::{GetBatchRequest, GetBatchResponse},
+    job_status::JobStatus,
+};
+
+use crate::{
+    routes::error::{batch_not_found, error_response},
+    scheduler::Scheduler,
+};
+
+/// The aggregate status of the jobs of a batch, or `None` if no job belongs to the batch.
+pub fn batch_response(sch: &Scheduler, batch: Uuid) -> Option<GetBatchResponse> {
+    let jobs = sch.get_batch_jobs(batch);
+    if jobs.is_empty() {
+        return None;
+    }
+
+    let mut counts: BTreeMap<String, usize> = JobStatus::NAMES
+        .iter()
+        .map(|name| (name.to_string(), 0))
+        .collect();
+    let mut done = 0.0;
+    let mut finished = true;
+    let mut created_at = None;
+    let mut updated_at = None;
+
+    for id in jobs.iter().copied() {
+        let (Some(status), Some(metadata)) = (sch.get_job_status(id), sch.get_job_metadata(id))
+        else {
+            continue;
+        };
+
+        *counts.entry(status.name().to_string()).or_default() += 1;
+
+        // Jobs that are not running have either done nothing yet, or all they will do
+        done += if status.is_finished() {
+            100.0
+        } else {
+            sch.get_job_progress(id).map_or(0.0, |p| p.percent)
+        };
+        finished &= status.is_finished();
+
+        // Jobs are sorted oldest first
+        created_at = created_at.or(Some(metadata.created_at));
+        updated_at = updated_at.max(Some(metadata.updated_at));
+    }
+
+    Some(GetBatchResponse {
+        uuid: batch,
+        progress: done / jobs.len() as f64,
+        jobs,
+        counts,
+        finished,
+        created_at: created_at?,
+        updated_at: updated_at?,
+    })
+}
+
+/// Request handler for getting the aggregate status of a batch.
+#[get("/getBatch")]
+pub async fn get_batch(
+    query: web::Query<GetBatchRequest>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match batch_response(&*sch.lock().await, query.uuid) {
+        Some(batch) => HttpResponse::Ok().json(batch),
+        None => error_response(StatusCode::BAD_REQUEST, batch_not_found(query.uuid)),
+    }
+}
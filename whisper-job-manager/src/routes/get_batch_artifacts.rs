@@ -0,0 +1,228 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use actix_web::{
+    get,
+    http::{
+        header::{ContentDisposition, DispositionParam, DispositionType},
+        StatusCode,
+    },
+    web, HttpResponse, Responder,
+};
+use anyhow::{Error, Result};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    batch::GetBatchArtifactsRequest,
+    error::{ErrorCode, ErrorResponse},
+    output_format::OutputFormat,
+};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::Config,
+    routes::error::{batch_not_found, error_response},
+    scheduler::Scheduler,
+};
+
+/// Why the files of a batch cannot be archived.
+#[derive(Debug)]
+pub enum BatchArchiveError {
+    /// No job belongs to the batch
+    UnknownBatch(Uuid),
+    /// No finished job of the batch produced a file of the formats asked for
+    NoArtifacts(Uuid),
+    /// Something went wrong on the server
+    Internal(Error),
+}
+
+impl BatchArchiveError {
+    /// The error sent to clients.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        match self {
+            BatchArchiveError::UnknownBatch(id) => batch_not_found(*id),
+            BatchArchiveError::NoArtifacts(_) => {
+                ErrorResponse::new(ErrorCode::OutputNotFound, self.to_string())
+            }
+            BatchArchiveError::Internal(_) => {
+                ErrorResponse::new(ErrorCode::Internal, self.to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for BatchArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchArchiveError::UnknownBatch(id) => write!(f, "Batch {} could not be found", id),
+            BatchArchiveError::NoArtifacts(id) => {
+                write!(
+                    f,
+                    "No finished job of batch {} produced a file to archive",
+                    id
+                )
+            }
+            BatchArchiveError::Internal(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+/// Where a file produced by a job is put in the archive: in the directory of the transcribed file within the storage directory, so files of
+/// different directories with the same name do not collide.
+fn entry_name(storage_path: &Path, source: Option<&Path>, filename: &str) -> String {
+    let directory = source
+        .and_then(Path::parent)
+        .and_then(|p| p.strip_prefix(storage_path).ok())
+        .unwrap_or(Path::new(""));
+    directory.join(filename).to_string_lossy().into_owned()
+}
+
+/// Collect the files the finished jobs of a batch produced, of the given format or of every format the jobs asked for, with their names in
+/// the archive.
+fn find_batch_artifacts(
+    sch: &Scheduler,
+    backend: &dyn TranscriptionBackend,
+    storage_path: &Path,
+    batch: Uuid,
+    format: Option<OutputFormat>,
+) -> Result<Vec<(String, PathBuf)>, BatchArchiveError> {
+    let jobs = sch.get_batch_jobs(batch);
+    if jobs.is_empty() {
+        return Err(BatchArchiveError::UnknownBatch(batch));
+    }
+
+    let mut names = HashSet::new();
+    let mut entries = vec![];
+
+    for id in jobs {
+        if !sch.get_job_status(id).is_some_and(|s| s.is_finished()) {
+            continue;
+        }
+        let Some(metadata) = sch.get_job_metadata(id) else {
+            continue;
+        };
+
+        let formats = match format {
+            Some(f) if metadata.output_formats.contains(&f) => vec![f],
+            Some(_) => continue,
+            None => metadata.output_formats,
+        };
+
+        let source = sch.get_job_source(id);
+        // The workspace of the job can be removed at any time, in which case its files are left out
+        for path in formats
+            .into_iter()
+            .filter_map(|format| backend.find_output(id, format))
+        {
+            let Some(filename) = path.file_name() else {
+                continue;
+            };
+
+            let mut name = entry_name(storage_path, source.as_deref(), &filename.to_string_lossy());
+            // The same file can be in a batch more than once
+            if !names.insert(name.clone()) {
+                name = format!("{}/{}", id, name);
+                names.insert(name.clone());
+            }
+
+            entries.push((name, path));
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(BatchArchiveError::NoArtifacts(batch));
+    }
+
+    Ok(entries)
+}
+
+/// Write the files to a ZIP archive in memory. Files removed since they were found are left out.
+fn write_archive(entries: Vec<(String, PathBuf)>) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, path) in entries {
+        let content = match std::fs::read(path.as_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                log::warn!("{:?} was removed before it could be archived", path);
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        archive.start_file(name, options)?;
+        archive.write_all(&content)?;
+    }
+
+    Ok(archive.finish()?.into_inner())
+}
+
+/// Archive the files the finished jobs of a batch produced, as a ZIP file. Jobs that are not finished are left out.
+pub async fn batch_archive(
+    sch: &Mutex<Scheduler>,
+    backend: &dyn TranscriptionBackend,
+    config: &Config,
+    batch: Uuid,
+    format: Option<OutputFormat>,
+) -> Result<HttpResponse, BatchArchiveError> {
+    let storage_path = std::fs::canonicalize(config.video_storage_path.as_str())
+        .map_err(|e| BatchArchiveError::Internal(e.into()))?;
+
+    let entries = find_batch_artifacts(
+        &*sch.lock().await,
+        backend,
+        storage_path.as_path(),
+        batch,
+        format,
+    )?;
+
+    // Compressing can take a while with many jobs, so it does not block the workers
+    let archive = web::block(move || write_archive(entries))
+        .await
+        .map_err(|e| BatchArchiveError::Internal(Error::msg(e.to_string())))?
+        .map_err(BatchArchiveError::Internal)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("batch-{}.zip", batch))],
+        })
+        .body(archive))
+}
+
+/// Request handler for downloading the files produced by the finished jobs of a batch, as a ZIP archive.
+#[get("/getBatchArtifacts")]
+pub async fn get_batch_artifacts(
+    query: web::Query<GetBatchArtifactsRequest>,
+    config: web::Data<Arc<Config>>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match batch_archive(
+        &sch,
+        backend.as_ref().as_ref(),
+        &config,
+        query.uuid,
+        query.format,
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            let status = match e {
+                BatchArchiveError::UnknownBatch(_) | BatchArchiveError::NoArtifacts(_) => {
+                    StatusCode::BAD_REQUEST
+                }
+                BatchArchiveError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, e.to_error_response())
+        }
+    }
+}
@@ -1,84 +1,170 @@
-use std::{ffi::OsString, path::PathBuf, sync::Arc};
+use std::{fmt, path::PathBuf, sync::Arc};
 
 use actix_files::NamedFile;
-use actix_web::{get, web, Either, HttpResponse, Responder};
+use actix_web::{get, http::StatusCode, mime::Mime, web, Either, HttpResponse, Responder};
 use tokio::sync::Mutex;
-use whisper_job_manager_models::GetJobRequest;
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    error::{ErrorCode, ErrorResponse},
+    job_status::JobStatus,
+    output_format::OutputFormat,
+    GetJobRequest,
+};
 
-use crate::{constants::TMP_DIR, scheduler::Scheduler};
+use crate::{
+    backend::TranscriptionBackend, routes::error::error_response, scheduler::Scheduler, workspace,
+};
 
-#[get("/getJob")]
-pub async fn get_job(
-    query: web::Query<GetJobRequest>,
-    sch: web::Data<Arc<Mutex<Scheduler>>>,
-) -> impl Responder {
-    let id = query.uuid;
+/// Why the output of a job cannot be served.
+#[derive(Debug)]
+pub enum JobOutputError {
+    /// The job cannot be found
+    UnknownJob,
+    /// The job is still queued or running
+    NotFinished,
+    /// The job did not ask for the format, only for the listed ones
+    FormatNotRequested(Vec<OutputFormat>),
+    /// The job did not ask for any format
+    NoFormats,
+    /// The workspace of a job that succeeded is gone
+    MissingWorkspace(PathBuf),
+    /// The job did not produce a file of the format
+    MissingOutput(OutputFormat),
+}
 
-    let sch = sch.lock().await;
+impl JobOutputError {
+    /// The error sent to clients.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        let code = match self {
+            JobOutputError::UnknownJob => ErrorCode::JobNotFound,
+            JobOutputError::NotFinished => ErrorCode::JobNotFinished,
+            JobOutputError::FormatNotRequested(_) => ErrorCode::FormatNotRequested,
+            JobOutputError::NoFormats | JobOutputError::MissingWorkspace(_) => ErrorCode::Internal,
+            JobOutputError::MissingOutput(_) => ErrorCode::OutputNotFound,
+        };
+        let error = ErrorResponse::new(code, self.to_string());
 
-    let status = sch.get_job_status(id);
-    if status.is_none() {
-        log::error!("Job {} could not be found", id);
-        return Either::Left(HttpResponse::BadRequest());
+        match self {
+            JobOutputError::FormatNotRequested(formats) => {
+                error.with_details(serde_json::json!({ "output_formats": formats }))
+            }
+            _ => error,
+        }
     }
-    let status = status.unwrap();
+}
 
-    if !status.is_finished() {
-        log::error!("Job {} is not finished", id);
-        return Either::Left(HttpResponse::BadRequest());
+impl fmt::Display for JobOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobOutputError::UnknownJob => write!(f, "The job could not be found"),
+            JobOutputError::NotFinished => write!(f, "The job is not finished"),
+            JobOutputError::FormatNotRequested(formats) => {
+                write!(
+                    f,
+                    "The job did not ask for that format, only for {:?}",
+                    formats
+                )
+            }
+            JobOutputError::NoFormats => write!(f, "The job did not ask for any format"),
+            JobOutputError::MissingWorkspace(path) => {
+                write!(f, "The job directory {:?} does not exist", path)
+            }
+            JobOutputError::MissingOutput(format) => {
+                write!(f, "The job did not produce a .{} file", format)
+            }
+        }
     }
+}
 
-    let mut job_path_dir = PathBuf::new();
-    job_path_dir.push(TMP_DIR.as_path());
-    job_path_dir.push(id.to_string());
+/// Find the file of the given format a finished job produced, or of the first format it asked for.
+pub fn find_job_output(
+    sch: &Scheduler,
+    backend: &dyn TranscriptionBackend,
+    id: Uuid,
+    format: Option<OutputFormat>,
+) -> Result<(OutputFormat, PathBuf), JobOutputError> {
+    let status = sch.get_job_status(id).ok_or(JobOutputError::UnknownJob)?;
 
-    if !job_path_dir.exists() || !job_path_dir.is_dir() {
-        log::error!("Job directory {:?} does not exist", job_path_dir);
-        return Either::Left(HttpResponse::InternalServerError());
+    if !status.is_finished() {
+        return Err(JobOutputError::NotFinished);
     }
 
-    let mut files = match std::fs::read_dir(job_path_dir.as_path()) {
-        Ok(f) => f,
-        Err(e) => {
-            log::error!("Could not get files of dir {:?}, {}", job_path_dir, e);
-            return Either::Left(HttpResponse::InternalServerError());
-        }
+    let output_formats = sch
+        .get_job_metadata(id)
+        .ok_or(JobOutputError::UnknownJob)?
+        .output_formats;
+
+    let format = match format {
+        Some(f) if output_formats.contains(&f) => f,
+        Some(_) => return Err(JobOutputError::FormatNotRequested(output_formats)),
+        None => *output_formats.first().ok_or(JobOutputError::NoFormats)?,
     };
 
-    // TODO support different files types other than .srt
-    let file = files.find(|f| {
-        if f.is_err() {
-            return false;
-        }
-        let f = f.as_ref().unwrap();
+    let job_path_dir = workspace::workspace_path(id);
 
-        let f_path = f.path();
-        let f_extension = f_path.extension();
-        if f_extension.is_none() {
-            return false;
-        }
-        let f_extension = f_extension.unwrap();
+    if !job_path_dir.exists() || !job_path_dir.is_dir() {
+        // Canceling a job removes its workspace, so only jobs that succeeded are expected to still have one
+        return Err(match status {
+            JobStatus::Succeeded => JobOutputError::MissingWorkspace(job_path_dir),
+            _ => JobOutputError::MissingOutput(format),
+        });
+    }
 
-        f_extension == &OsString::from("srt")
-    });
+    let file_path = backend
+        .find_output(id, format)
+        .ok_or(JobOutputError::MissingOutput(format))?;
 
-    if let Some(file) = file {
-        // ok to unwrap given the find() call above
-        let file = file.unwrap();
-        let file_path = file.path();
-        match NamedFile::open(file_path.as_path()) {
-            Ok(f) => Either::Right(f),
+    Ok((format, file_path))
+}
+
+/// Send an output file with the content type of its format.
+pub fn serve_output(file_path: PathBuf, format: OutputFormat) -> Either<HttpResponse, NamedFile> {
+    match NamedFile::open(file_path.as_path()) {
+        Ok(f) => match format.content_type().parse::<Mime>() {
+            Ok(content_type) => Either::Right(f.set_content_type(content_type)),
             Err(e) => {
-                log::error!("Could not open file {:?}: {}", file_path, e);
-                Either::Left(HttpResponse::InternalServerError())
+                log::warn!("Invalid content type for format {}: {}", format, e);
+                Either::Right(f)
             }
+        },
+        Err(e) => Either::Left(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorResponse::new(
+                ErrorCode::Internal,
+                format!("Could not open file {:?}: {}", file_path, e),
+            ),
+        )),
+    }
+}
+
+#[get("/getJob")]
+pub async fn get_job(
+    query: web::Query<GetJobRequest>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = query.uuid;
+
+    let output = find_job_output(
+        &*sch.lock().await,
+        backend.as_ref().as_ref(),
+        id,
+        query.format,
+    );
+
+    match output {
+        Ok((format, file_path)) => serve_output(file_path, format),
+        Err(e) => {
+            let status = match e {
+                JobOutputError::UnknownJob
+                | JobOutputError::NotFinished
+                | JobOutputError::FormatNotRequested(_) => StatusCode::BAD_REQUEST,
+                JobOutputError::NoFormats | JobOutputError::MissingWorkspace(_) => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+                JobOutputError::MissingOutput(_) => StatusCode::NOT_FOUND,
+            };
+            Either::Left(error_response(status, e.to_error_response()))
         }
-    } else {
-        log::error!(
-            "No .srt file found in {:?}, files: {:?}",
-            job_path_dir,
-            files
-        );
-        Either::Left(HttpResponse::InternalServerError())
     }
 }
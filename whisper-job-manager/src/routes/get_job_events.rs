@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use actix_web::{
+    get,
+    http::{header, StatusCode},
+    web, HttpResponse, Responder,
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::{job_event::JobEvent, GetJobEventsRequest};
+
+use crate::{
+    job_events,
+    routes::error::{error_response, job_not_found},
+    scheduler::Scheduler,
+};
+
+/// Respond with the events of the given job, or of every job, as server-sent events. Returns `None` if the job cannot be found.
+pub async fn stream_events(
+    sch: &Arc<Mutex<Scheduler>>,
+    uuid: Option<Uuid>,
+) -> Option<HttpResponse> {
+    let (events, initial) = {
+        let sch = sch.lock().await;
+
+        // Subscribe while holding the lock, so no change is missed between the current status and the first event
+        let initial = match uuid {
+            Some(id) => Some(JobEvent::status_changed(id, sch.get_job_status(id)?)),
+            None => None,
+        };
+        (sch.subscribe_to_job_events(), initial)
+    };
+
+    let body = job_events::event_stream(events, uuid, initial, Arc::clone(sch));
+
+    Some(
+        HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .streaming(body),
+    )
+}
+
+/// Request handler for following the lifecycle of a job, or of every job, as server-sent events. Each event is named after its kind, and
+/// its data is the event as JSON. Following a single job starts with its current status, and ends once it is finished.
+#[get("/getJobEvents")]
+pub async fn get_job_events(
+    query: web::Query<GetJobEventsRequest>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match stream_events(sch.get_ref(), query.uuid).await {
+        Some(response) => response,
+        // Only a followed job can be missing
+        None => error_response(
+            StatusCode::BAD_REQUEST,
+            job_not_found(query.uuid.unwrap_or_default()),
+        ),
+    }
+}
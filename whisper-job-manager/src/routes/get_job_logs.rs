@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use actix_web::{
+    get,
+    http::{header::ContentType, StatusCode},
+    web, HttpResponse, Responder,
+};
+use futures_util::StreamExt;
+use tokio::sync::Mutex;
+use whisper_job_manager_models::{
+    error::{ErrorCode, ErrorResponse},
+    job_log::LogStream,
+    GetJobLogsRequest, GetJobLogsResponse,
+};
+
+use crate::{
+    logs,
+    routes::error::{error_response, job_not_found},
+    scheduler::Scheduler,
+};
+
+/// Request handler for reading the logs of a job, or following them while it runs.
+#[get("/getJobLogs")]
+pub async fn get_job_logs(
+    query: web::Query<GetJobLogsRequest>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = query.uuid;
+
+    if sch.lock().await.get_job_status(id).is_none() {
+        return error_response(StatusCode::BAD_REQUEST, job_not_found(id));
+    }
+
+    if query.offset.is_some() && query.stream == LogStream::Both {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new(
+                ErrorCode::InvalidRequest,
+                "An offset needs a single stream to read from",
+            ),
+        );
+    }
+
+    let mut job_logs = Vec::with_capacity(2);
+    for stream in query.stream.streams() {
+        match logs::read_log(id, *stream, query.offset, query.tail).await {
+            Ok(l) => job_logs.push(l),
+            Err(e) => {
+                return error_response(
+                    StatusCode::NOT_FOUND,
+                    ErrorResponse::new(
+                        ErrorCode::LogNotFound,
+                        format!("Could not read {} of job {}: {}", stream, id, e),
+                    ),
+                );
+            }
+        }
+    }
+
+    if !query.follow {
+        return HttpResponse::Ok().json(GetJobLogsResponse { logs: job_logs });
+    }
+
+    // Send what was read so far, then follow from where it ended
+    let start = job_logs
+        .iter()
+        .map(|l| l.content.clone())
+        .collect::<String>();
+    let streams = job_logs.iter().map(|l| (l.stream, l.next_offset)).collect();
+    let sch = Arc::clone(sch.get_ref());
+
+    let body =
+        futures_util::stream::once(
+            async move { Ok::<_, actix_web::Error>(web::Bytes::from(start)) },
+        )
+        .chain(logs::follow_logs(id, streams, sch));
+
+    HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .streaming(body)
+}
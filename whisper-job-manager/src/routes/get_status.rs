@@ -1,10 +1,35 @@
 use std::sync::Arc;
 
-use actix_web::{get, web, HttpResponse, Responder};
+use actix_web::{get, http::StatusCode, web, HttpResponse, Responder};
 use tokio::sync::Mutex;
+use uuid::Uuid;
 use whisper_job_manager_models::{GetStatusRequest, GetStatusResponse};
 
-use crate::scheduler::Scheduler;
+use crate::{
+    routes::error::{error_response, job_not_found},
+    scheduler::Scheduler,
+};
+
+/// The status of a job as reported to clients, or `None` if the job cannot be found.
+pub fn status_response(sch: &Scheduler, uuid: Uuid) -> Option<GetStatusResponse> {
+    let status = sch.get_job_status(uuid)?;
+
+    let Some(metadata) = sch.get_job_metadata(uuid) else {
+        log::warn!("Metadata of job with ID {} cannot be found", uuid);
+        return None;
+    };
+
+    let progress = sch.get_job_progress(uuid);
+
+    Some(GetStatusResponse {
+        uuid,
+        status,
+        metadata,
+        priority: sch.get_job_priority(uuid).unwrap_or_default(),
+        progress: progress.map(|p| p.percent),
+        eta: progress.and_then(|p| p.eta_secs),
+    })
+}
 
 #[get("/getStatus")]
 pub async fn get_status(
@@ -13,24 +38,8 @@ pub async fn get_status(
 ) -> impl Responder {
     let uuid = query.uuid;
 
-    let sch_guard = sch.lock().await;
-
-    let status = sch_guard.get_job_status(uuid);
-
-    if status.is_none() {
-        log::error!("Status of job with ID {} cannot be found", uuid);
-        return HttpResponse::BadRequest().into();
+    match status_response(&*sch.lock().await, uuid) {
+        Some(status) => HttpResponse::Ok().json(status),
+        None => error_response(StatusCode::BAD_REQUEST, job_not_found(uuid)),
     }
-
-    let metadata = sch_guard.get_job_metadata(uuid);
-
-    if metadata.is_none() {
-        log::error!("Metadata of job with ID {} cannot be found", uuid);
-        return HttpResponse::BadRequest().into();
-    }
-
-    HttpResponse::Ok().json(GetStatusResponse {
-        status: status.unwrap(),
-        metadata: metadata.unwrap(),
-    })
 }
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use actix_web::{get, http::StatusCode, web, HttpResponse, Responder};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    error::{ErrorCode, ErrorResponse},
+    webhook::{GetWebhookDeliveriesRequest, GetWebhookDeliveriesResponse},
+};
+
+use crate::{
+    config::Config,
+    routes::error::{error_response, job_not_found},
+    scheduler::Scheduler,
+    webhooks,
+};
+
+/// Respond with the webhook deliveries of a known job, or `None` if the job is unknown.
+pub async fn deliveries_response(
+    sch: &Mutex<Scheduler>,
+    config: &Config,
+    id: Uuid,
+) -> Option<HttpResponse> {
+    sch.lock().await.get_job_status(id)?;
+
+    let response = match webhooks::read_deliveries(&config.webhooks.delivery_log_path, id).await {
+        Ok(deliveries) => HttpResponse::Ok().json(GetWebhookDeliveriesResponse { deliveries }),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorResponse::new(
+                ErrorCode::Internal,
+                format!("Could not read webhook deliveries of job {}: {:#}", id, e),
+            ),
+        ),
+    };
+
+    Some(response)
+}
+
+/// Request handler for reading the attempts at calling the webhooks of a job.
+#[get("/getWebhookDeliveries")]
+pub async fn get_webhook_deliveries(
+    query: web::Query<GetWebhookDeliveriesRequest>,
+    config: web::Data<Arc<Config>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = query.uuid;
+
+    match deliveries_response(sch.get_ref(), config.get_ref(), id).await {
+        Some(response) => response,
+        None => error_response(StatusCode::BAD_REQUEST, job_not_found(id)),
+    }
+}
@@ -1,13 +1,16 @@
-use std::path::PathBuf;
-
+pub mod cancel_batch;
 pub mod cancel_job;
+pub mod error;
 pub mod get_all_statuses;
+pub mod get_artifacts;
+pub mod get_batch;
+pub mod get_batch_artifacts;
 pub mod get_job;
+pub mod get_job_events;
+pub mod get_job_logs;
 pub mod get_status;
+pub mod get_webhook_deliveries;
+pub mod new_batch;
 pub mod new_job;
-
-async fn cleanup_workspace(workspace_path: PathBuf) {
-    if let Err(e) = tokio::fs::remove_dir_all(workspace_path.as_path()).await {
-        log::error!("Failed to remove {:?}: {}", workspace_path, e);
-    }
-}
+pub mod upload_job;
+pub mod v2;
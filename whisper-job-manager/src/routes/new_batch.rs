@@ -0,0 +1,221 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use actix_web::{http::StatusCode, post, web, HttpRequest, HttpResponse, Responder};
+use anyhow::{Context, Error, Result};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::batch::{NewBatchRequest, NewBatchResponse};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::Config,
+    routes::{
+        error::error_response,
+        new_job::{build_spec, get_full_path_of_file_to_transcribe, new_job_metadata, NewJobError},
+    },
+    scheduler::Scheduler,
+    workspace,
+};
+
+/// The pattern of the files of a directory batched when the request only gives the directory
+const DEFAULT_GLOB: &str = "*";
+
+/// Find the files of the storage directory a batch asks for, either as a list of paths or as a directory and a glob. Files found with a glob
+/// are sorted by path.
+fn find_batch_files(storage_path: &Path, request: &NewBatchRequest) -> Result<Vec<PathBuf>> {
+    if !request.paths.is_empty() {
+        if request.directory.is_some() || request.glob.is_some() {
+            return Err(Error::msg(
+                "paths cannot be combined with directory and glob",
+            ));
+        }
+
+        return request
+            .paths
+            .iter()
+            .map(|path| {
+                get_full_path_of_file_to_transcribe(storage_path, path)
+                    .with_context(|| format!("Could not find file {} in {:?}", path, storage_path))
+            })
+            .collect();
+    }
+
+    if request.directory.is_none() && request.glob.is_none() {
+        return Err(Error::msg(
+            "A batch needs either paths, or a directory or a glob",
+        ));
+    }
+
+    let directory =
+        std::fs::canonicalize(storage_path.join(request.directory.as_deref().unwrap_or("")))
+            .with_context(|| {
+                format!(
+                    "Could not find directory {:?} in {:?}",
+                    request.directory, storage_path
+                )
+            })?;
+    if !directory.starts_with(storage_path) || !directory.is_dir() {
+        return Err(Error::msg(format!(
+            "{:?} is not a directory of {:?}",
+            directory, storage_path
+        )));
+    }
+
+    let glob = request.glob.as_deref().unwrap_or(DEFAULT_GLOB);
+    if Path::new(glob).is_absolute() {
+        return Err(Error::msg(format!(
+            "Glob {:?} must be relative to the directory",
+            glob
+        )));
+    }
+
+    // The directory is escaped so only the glob is a pattern
+    let pattern = format!(
+        "{}/{}",
+        glob::Pattern::escape(&directory.to_string_lossy()),
+        glob
+    );
+
+    let mut files = vec![];
+    for entry in glob::glob(&pattern).with_context(|| format!("Invalid glob {:?}", glob))? {
+        let path = entry?;
+        if !path.is_file() {
+            continue;
+        }
+
+        // Globs can leave the storage directory with `..` or through links
+        let path = std::fs::canonicalize(path.as_path())?;
+        if path.starts_with(storage_path) {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    files.dedup();
+
+    if files.is_empty() {
+        return Err(Error::msg(format!(
+            "No file matches {:?} in {:?}",
+            glob, directory
+        )));
+    }
+
+    Ok(files)
+}
+
+/// Queue a job for every file a batch asks for, and return the UUIDs of the batch and of its jobs. Either every job of the batch is queued,
+/// or none is, e.g. if a file cannot be found, the options are invalid or a workspace cannot be created.
+pub async fn create_batch(
+    req: &HttpRequest,
+    request: &NewBatchRequest,
+    config: &Config,
+    backend: &dyn TranscriptionBackend,
+    sch: &Mutex<Scheduler>,
+) -> Result<(Uuid, Vec<Uuid>), NewJobError> {
+    let storage_path = std::fs::canonicalize(config.video_storage_path.as_str()).map_err(|e| {
+        NewJobError::Internal(Error::msg(format!(
+            "Could not find canonical path for {:?}: {}",
+            config.video_storage_path, e
+        )))
+    })?;
+
+    let files =
+        find_batch_files(storage_path.as_path(), request).map_err(NewJobError::InvalidPath)?;
+
+    if files.len() > config.batches.max_jobs {
+        return Err(NewJobError::InvalidJob(Error::msg(format!(
+            "The batch has {} files, but a batch can have at most {} jobs",
+            files.len(),
+            config.batches.max_jobs
+        ))));
+    }
+
+    let batch_id = Uuid::new_v4();
+
+    let specs = files
+        .into_iter()
+        .map(|file| {
+            let mut spec = build_spec(req, &request.options, file, config, backend)?;
+            spec.batch_id = Some(batch_id);
+            Ok(spec)
+        })
+        .collect::<Result<Vec<_>>>()
+        .map_err(NewJobError::InvalidJob)?;
+
+    // Every job is prepared before any is queued, so a failure leaves nothing behind
+    let mut jobs = Vec::with_capacity(specs.len());
+    for spec in specs {
+        let uuid = Uuid::new_v4();
+        let prepared = match workspace::setup_workspace(uuid).await {
+            Ok(_) => new_job_metadata(&spec)
+                .await
+                .map(|metadata| (uuid, spec, metadata)),
+            Err(e) => Err(NewJobError::Internal(Error::msg(format!(
+                "Error creating workspace: {}",
+                e
+            )))),
+        };
+
+        match prepared {
+            Ok(job) => jobs.push(job),
+            Err(e) => {
+                // The workspace of the job that failed is removed as well, if it was created
+                cleanup_workspaces(jobs.iter().map(|(id, _, _)| *id).chain([uuid])).await;
+                return Err(e);
+            }
+        }
+    }
+
+    // The scheduler stays locked while the jobs are queued, so a shutdown cannot start halfway through the batch
+    let mut sch = sch.lock().await;
+
+    if sch.is_draining() {
+        drop(sch);
+        cleanup_workspaces(jobs.iter().map(|(id, _, _)| *id)).await;
+        return Err(NewJobError::ShuttingDown);
+    }
+
+    let ids: Vec<Uuid> = jobs.iter().map(|(id, _, _)| *id).collect();
+    for (uuid, spec, metadata) in jobs {
+        sch.queue_new_job((uuid, spec), metadata);
+    }
+
+    log::info!("Queued batch {} with {} jobs", batch_id, ids.len());
+
+    Ok((batch_id, ids))
+}
+
+/// Remove the workspaces of the jobs of a batch that could not be queued.
+async fn cleanup_workspaces(ids: impl Iterator<Item = Uuid>) {
+    for id in ids {
+        let path = workspace::workspace_path(id);
+        if path.exists() {
+            workspace::cleanup_workspace(path).await;
+        }
+    }
+}
+
+/// Request handler for queueing a job for every file of a list, or of a directory.
+#[post("/newBatch")]
+pub async fn new_batch(
+    req: HttpRequest,
+    json: web::Json<NewBatchRequest>,
+    config: web::Data<Arc<Config>>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match create_batch(&req, &json, &config, backend.as_ref().as_ref(), &sch).await {
+        Ok((uuid, jobs)) => HttpResponse::Ok().json(NewBatchResponse { uuid, jobs }),
+        Err(e) => {
+            let status = match e {
+                NewJobError::InvalidPath(_) | NewJobError::InvalidJob(_) => StatusCode::BAD_REQUEST,
+                NewJobError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+                NewJobError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, e.to_error_response())
+        }
+    }
+}
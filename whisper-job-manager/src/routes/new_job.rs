@@ -1,42 +1,30 @@
 use std::{
+    fmt,
     path::{Path, PathBuf},
-    process::Stdio,
     sync::Arc,
 };
 
-use actix_web::{post, web, HttpResponse, Responder};
-use anyhow::{Error, Result};
-use tokio::{process::Command, sync::Mutex};
+use actix_web::{http::StatusCode, post, web, HttpRequest, HttpResponse, Responder};
+use anyhow::{Context, Error, Result};
+use tokio::sync::Mutex;
 use uuid::Uuid;
-use whisper_job_manager_models::{job_metadata::JobMetadata, NewJobRequest, NewJobResponse};
-
-use crate::{config::Config, constants::TMP_DIR, scheduler::Scheduler};
-
-const STDOUT_FILE: &'static str = "out.txt";
-const STDERR_FILE: &'static str = "err.txt";
-
-async fn setup_workspace(uuid: Uuid) -> tokio::io::Result<(PathBuf, PathBuf, PathBuf)> {
-    // Create directory for this job
-    let mut workspace = TMP_DIR.clone();
-    workspace.push(uuid.to_string());
-    tokio::fs::create_dir_all(workspace.as_path()).await?;
-
-    // TODO try to use tokio file handles so we don't have to repoen file
-
-    // Create stdio
-    let mut stdout_path = workspace.clone();
-    stdout_path.push(STDOUT_FILE);
-    tokio::fs::File::create(stdout_path.as_path()).await?;
-
-    // Create stderr
-    let mut stderr_path = workspace.clone();
-    stderr_path.push(STDERR_FILE);
-    tokio::fs::File::create(stderr_path.as_path()).await?;
+use whisper_job_manager_models::{
+    error::{ErrorCode, ErrorResponse},
+    job_metadata::JobMetadata,
+    output_format::OutputFormat,
+    NewJobOptions, NewJobRequest, NewJobResponse,
+};
 
-    Ok((workspace, stdout_path, stderr_path))
-}
+use crate::{
+    backend::TranscriptionBackend,
+    config::{validate_webhook_url, Config, SubmitterConfig},
+    media,
+    routes::error::error_response,
+    scheduler::{job_spec::JobSpec, Scheduler},
+    workspace,
+};
 
-fn get_full_path_of_file_to_transcribe<P: AsRef<Path>>(
+pub fn get_full_path_of_file_to_transcribe<P: AsRef<Path>>(
     storage_canonical_path: P,
     file_path_str: &str,
 ) -> Result<PathBuf> {
@@ -73,100 +61,356 @@ fn get_full_path_of_file_to_transcribe<P: AsRef<Path>>(
     Ok(file_path_canonical_path)
 }
 
-#[post("/newJob")]
-pub async fn new_job(
-    json: web::Json<NewJobRequest>,
-    config: web::Data<Arc<Config>>,
-    sch: web::Data<Arc<Mutex<Scheduler>>>,
-) -> impl Responder {
-    let uuid = Uuid::new_v4();
+/// Create the spec of a job transcribing the given file, with the options the job asked for. Other options are configured by the
+/// scheduler strategy.
+pub fn build_spec(
+    req: &HttpRequest,
+    options: &NewJobOptions,
+    source: PathBuf,
+    config: &Config,
+    backend: &dyn TranscriptionBackend,
+) -> Result<JobSpec> {
+    let mut spec = JobSpec::new(source);
+    spec.retry = match &options.retry {
+        Some(policy) => config
+            .retry
+            .with_overrides(policy)
+            .with_context(|| format!("Invalid retry policy {:?}", policy))?,
+        None => config.retry.clone(),
+    };
+    if options.max_runtime_secs == Some(0) || options.max_queue_time_secs == Some(0) {
+        return Err(Error::msg("Time limits of a job must be greater than 0"));
+    }
+    spec.language = config
+        .transcription
+        .resolve_language(options.language.as_deref())
+        .context("Invalid language")?;
+    if let Some(model) = &options.model {
+        config
+            .transcription
+            .validate_model(model)
+            .context("Invalid model")?;
+        spec.model = Some(model.clone());
+    }
+    spec.task = options.task.unwrap_or_default();
+    if let Some(formats) = &options.output_formats {
+        if formats.is_empty() {
+            return Err(Error::msg("A job must ask for at least one output format"));
+        }
+        let unsupported = formats
+            .iter()
+            .find(|f| **f != OutputFormat::All && !backend.supports_format(**f));
+        if let Some(format) = unsupported {
+            return Err(Error::msg(format!(
+                "The transcription backend cannot write {} files",
+                format
+            )));
+        }
+        // Asking for all formats means every format the backend can write
+        spec.output_formats = OutputFormat::expand(formats)
+            .into_iter()
+            .filter(|f| backend.supports_format(*f))
+            .collect();
+    }
+    spec.max_runtime_secs = options
+        .max_runtime_secs
+        .or(config.timeouts.max_runtime_secs);
+    spec.max_queue_time_secs = options
+        .max_queue_time_secs
+        .or(config.timeouts.max_queue_time_secs);
+    if let Some(start) = options.start_secs {
+        if !start.is_finite() || start < 0.0 {
+            return Err(Error::msg(
+                "start_secs must be a positive number of seconds",
+            ));
+        }
+    }
+    if let Some(end) = options.end_secs {
+        if !end.is_finite() || end <= options.start_secs.unwrap_or(0.0) {
+            return Err(Error::msg("end_secs must be after start_secs"));
+        }
+    }
+    let has_range = options.start_secs.is_some() || options.end_secs.is_some();
+    // Cutting the source to a range is done while preprocessing it
+    if has_range && options.preprocess == Some(false) {
+        return Err(Error::msg(
+            "Transcribing part of a file needs preprocessing, which the job disabled",
+        ));
+    }
+    if options.preprocess.unwrap_or(config.preprocessing.enabled) || has_range {
+        spec.preprocessing = Some(config.preprocessing.params.clone());
+        spec.start_secs = options.start_secs;
+        spec.end_secs = options.end_secs;
+    }
+    if let Some(tags) = &options.tags {
+        if let Some(tag) = tags.iter().find(|t| t.trim().is_empty() || t.contains(',')) {
+            return Err(Error::msg(format!(
+                "Invalid tag {:?}, tags cannot be blank or contain commas",
+                tag
+            )));
+        }
+        spec.tags = tags.clone();
+    }
+    if let Some(url) = &options.callback_url {
+        validate_webhook_url(url).context("Invalid callback_url")?;
+        spec.callback_url = Some(url.clone());
+    }
+    spec.priority = options.priority.unwrap_or_default();
+    spec.submitter = submitter(req, options, &config.submitters);
+
+    Ok(spec)
+}
+
+/// Who submits a job: the identity header set by the proxy in front of the server, then the submitter the client gives if clients are
+/// trusted, then the address of the client.
+fn submitter(req: &HttpRequest, options: &NewJobOptions, config: &SubmitterConfig) -> String {
+    let from_header = config.identity_header.as_ref().and_then(|name| {
+        req.headers()
+            .get(name.as_str())
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    });
+    if let Some(submitter) = from_header {
+        return submitter.to_string();
+    }
+
+    match &options.submitter {
+        Some(s) if config.trust_clients => s.clone(),
+        _ => req
+            .peer_addr()
+            .map(|a| a.ip().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Why a job could not be queued.
+#[derive(Debug)]
+pub enum NewJobError {
+    /// The file to transcribe cannot be found in the storage directory
+    InvalidPath(Error),
+    /// The options of the job are invalid
+    InvalidJob(Error),
+    /// The server is shutting down and does not accept new jobs
+    ShuttingDown,
+    /// Something went wrong on the server
+    Internal(Error),
+}
+
+impl NewJobError {
+    /// The error sent to clients.
+    pub fn to_error_response(&self) -> ErrorResponse {
+        let code = match self {
+            NewJobError::InvalidPath(_) => ErrorCode::InvalidPath,
+            NewJobError::InvalidJob(_) => ErrorCode::InvalidJob,
+            NewJobError::ShuttingDown => ErrorCode::ShuttingDown,
+            NewJobError::Internal(_) => ErrorCode::Internal,
+        };
+        ErrorResponse::new(code, self.to_string())
+    }
+}
+
+impl fmt::Display for NewJobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NewJobError::InvalidPath(e) => write!(f, "Invalid path: {:#}", e),
+            NewJobError::InvalidJob(e) => write!(f, "Invalid job: {:#}", e),
+            NewJobError::ShuttingDown => write!(f, "The server is shutting down"),
+            NewJobError::Internal(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+/// Build the metadata of a job queued with the given spec. The file is probed, which runs a process, so this is done before locking the
+/// scheduler.
+pub async fn new_job_metadata(spec: &JobSpec) -> Result<JobMetadata, NewJobError> {
+    let duration_secs = media::probe_duration(spec.source.as_path()).await;
+
+    let Some(filename) = spec.source.file_name() else {
+        return Err(NewJobError::Internal(Error::msg(format!(
+            "Error creating metadata, cannot find filename for {:?}",
+            spec.source
+        ))));
+    };
+    let filename = PathBuf::from(filename.to_os_string());
+
+    let mut metadata = JobMetadata::init_for_queued_job(filename);
+    metadata.language = spec.language.clone();
+    metadata.model = spec.model.clone();
+    metadata.task = spec.task;
+    metadata.output_formats = spec.output_formats.clone();
+    metadata.duration_secs = duration_secs;
+    metadata.start_secs = spec.start_secs;
+    metadata.end_secs = spec.end_secs;
+    metadata.tags = spec.tags.clone();
+    metadata.batch_id = spec.batch_id;
 
-    let (workspace_path, stdout_filepath, stderr_filepath) = match setup_workspace(uuid).await {
-        Ok(files) => files,
+    Ok(metadata)
+}
+
+/// Queue a job with the given spec, whose workspace is already set up. The workspace is removed if the job cannot be queued.
+pub async fn queue_job(
+    uuid: Uuid,
+    spec: JobSpec,
+    workspace_path: PathBuf,
+    sch: &Mutex<Scheduler>,
+) -> Result<(), NewJobError> {
+    let metadata = match new_job_metadata(&spec).await {
+        Ok(m) => m,
         Err(e) => {
-            log::error!("Error creating workspace: {}", e);
-            return HttpResponse::InternalServerError().into();
+            workspace::cleanup_workspace(workspace_path).await;
+            return Err(e);
         }
     };
 
-    let mut storage_path = PathBuf::new();
-    storage_path.push(config.video_storage_path.clone());
-    storage_path = match std::fs::canonicalize(storage_path.clone()) {
+    let mut sch = sch.lock().await;
+
+    if sch.is_draining() {
+        workspace::cleanup_workspace(workspace_path).await;
+        return Err(NewJobError::ShuttingDown);
+    }
+
+    sch.queue_new_job((uuid, spec), metadata);
+
+    Ok(())
+}
+
+/// Queue a job transcribing a file of the storage directory, and return its UUID.
+pub async fn create_job(
+    req: &HttpRequest,
+    request: &NewJobRequest,
+    config: &Config,
+    backend: &dyn TranscriptionBackend,
+    sch: &Mutex<Scheduler>,
+) -> Result<Uuid, NewJobError> {
+    let uuid = Uuid::new_v4();
+
+    let workspace_path = workspace::setup_workspace(uuid).await.map_err(|e| {
+        NewJobError::Internal(Error::msg(format!("Error creating workspace: {}", e)))
+    })?;
+
+    let storage_path = match std::fs::canonicalize(config.video_storage_path.as_str()) {
         Ok(s) => s,
         Err(e) => {
-            log::error!(
+            workspace::cleanup_workspace(workspace_path).await;
+            return Err(NewJobError::Internal(Error::msg(format!(
                 "Could not find canonical path for {:?}: {}",
-                storage_path,
-                e
-            );
-            return HttpResponse::InternalServerError().into();
+                config.video_storage_path, e
+            ))));
         }
     };
 
     let file_to_transcribe_path =
-        match get_full_path_of_file_to_transcribe(storage_path.as_path(), &json.path) {
+        match get_full_path_of_file_to_transcribe(storage_path.as_path(), &request.path) {
             Ok(f) => f,
             Err(e) => {
-                log::error!(
-                    "Could not find file {} in {:?}: {}",
-                    json.path,
-                    storage_path.as_path(),
-                    e
-                );
-                super::cleanup_workspace(workspace_path).await;
-                return HttpResponse::InternalServerError().into();
+                workspace::cleanup_workspace(workspace_path).await;
+                return Err(NewJobError::InvalidPath(e.context(format!(
+                    "Could not find file {} in {:?}",
+                    request.path, storage_path
+                ))));
             }
         };
 
-    let stdout_file = match std::fs::File::create(stdout_filepath.as_path()) {
-        Ok(f) => f,
+    let spec = match build_spec(
+        req,
+        &request.options,
+        file_to_transcribe_path,
+        config,
+        backend,
+    ) {
+        Ok(s) => s,
         Err(e) => {
-            log::error!("Error opening {:?}: {}", stdout_filepath.as_path(), e);
-            super::cleanup_workspace(workspace_path).await;
-            return HttpResponse::InternalServerError().into();
+            workspace::cleanup_workspace(workspace_path).await;
+            return Err(NewJobError::InvalidJob(e));
         }
     };
 
-    let stderr_file = match std::fs::File::create(stderr_filepath.as_path()) {
-        Ok(f) => f,
+    queue_job(uuid, spec, workspace_path, sch).await?;
+
+    Ok(uuid)
+}
+
+#[post("/newJob")]
+pub async fn new_job(
+    req: HttpRequest,
+    json: web::Json<NewJobRequest>,
+    config: web::Data<Arc<Config>>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match create_job(&req, &json, &config, backend.as_ref().as_ref(), &sch).await {
+        Ok(uuid) => HttpResponse::Ok().json(NewJobResponse { uuid }),
         Err(e) => {
-            log::error!("Error opening {:?}: {}", stderr_filepath.as_path(), e);
-            super::cleanup_workspace(workspace_path).await;
-            return HttpResponse::InternalServerError().into();
+            let status = match e {
+                // Kept as it was for existing clients, the v2 API answers with 422
+                NewJobError::InvalidPath(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                NewJobError::InvalidJob(_) => StatusCode::BAD_REQUEST,
+                NewJobError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+                NewJobError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, e.to_error_response())
         }
-    };
+    }
+}
 
-    // Create default command that says basic universal parameters, like output path and what file to use. Other options should be configured
-    // in the scheduler strategy
-    let mut cmd = Command::new("whisper");
-    cmd.arg("--output_dir")
-        .arg(workspace_path.as_path())
-        .arg("--output_format")
-        .arg("srt")
-        .arg("--language")
-        .arg("fr")
-        .arg(file_to_transcribe_path.as_path())
-        .stdout(Stdio::from(stdout_file))
-        .stderr(Stdio::from(stderr_file))
-        .kill_on_drop(true);
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
 
-    let mut sch = sch.lock().await;
+    use super::*;
 
-    // unwrap ok because we already validated that this path exists is a file
-    let filename = file_to_transcribe_path.file_name();
-    if filename.is_none() {
-        log::error!(
-            "Error creating metadata, cannot find filename for {:?}",
-            file_to_transcribe_path
-        );
-        super::cleanup_workspace(workspace_path).await;
-        return HttpResponse::InternalServerError().into();
+    fn request(identity: Option<&str>) -> HttpRequest {
+        let mut request = TestRequest::default().peer_addr("10.0.0.1:1234".parse().unwrap());
+        if let Some(identity) = identity {
+            request = request.insert_header(("X-Remote-User", identity));
+        }
+        request.to_http_request()
     }
-    let filename = PathBuf::from(filename.unwrap().to_os_string());
 
-    let metadata = JobMetadata::init_for_queued_job(filename);
+    fn options(submitter: Option<&str>) -> NewJobOptions {
+        NewJobOptions {
+            submitter: submitter.map(String::from),
+            ..NewJobOptions::default()
+        }
+    }
 
-    sch.queue_new_job((uuid, cmd), metadata);
+    #[test]
+    fn ignores_submitters_given_by_untrusted_clients() {
+        let config = SubmitterConfig::default();
+        assert_eq!(
+            submitter(&request(None), &options(Some("alice")), &config),
+            "10.0.0.1"
+        );
+    }
 
-    HttpResponse::Ok().json(NewJobResponse { uuid })
+    #[test]
+    fn takes_submitters_from_trusted_sources() {
+        let trusted = SubmitterConfig {
+            trust_clients: true,
+            ..SubmitterConfig::default()
+        };
+        assert_eq!(
+            submitter(&request(None), &options(Some("alice")), &trusted),
+            "alice"
+        );
+        assert_eq!(
+            submitter(&request(None), &options(None), &trusted),
+            "10.0.0.1"
+        );
+
+        // The proxy knows better than the client
+        let proxied = SubmitterConfig {
+            identity_header: Some(String::from("x-remote-user")),
+            trust_clients: true,
+        };
+        assert_eq!(
+            submitter(&request(Some("bob")), &options(Some("alice")), &proxied),
+            "bob"
+        );
+        assert_eq!(
+            submitter(&request(Some(" ")), &options(Some("alice")), &proxied),
+            "alice"
+        );
+    }
 }
@@ -0,0 +1,269 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use actix_multipart::{Field, Multipart, MultipartError};
+use actix_web::{
+    http::{header, StatusCode},
+    post, web, HttpRequest, HttpResponse, Responder,
+};
+use futures_util::StreamExt;
+use tokio::{io::AsyncWriteExt, sync::Mutex};
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    error::{ErrorCode, ErrorResponse},
+    NewJobOptions, NewJobResponse,
+};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::{Config, UploadConfig},
+    routes::{
+        error::error_response,
+        new_job::{self, NewJobError},
+    },
+    scheduler::Scheduler,
+    workspace,
+};
+
+/// The largest `options` part accepted, in bytes
+const MAX_OPTIONS_SIZE: usize = 64 * 1024;
+
+/// Why an uploaded file could not be saved.
+enum UploadError {
+    /// The file has no name
+    MissingFilename,
+    /// The file has a content type that is not allowed, or none
+    ContentType(Option<String>),
+    /// The file is larger than allowed
+    TooLarge,
+    /// The request could not be read
+    Payload(MultipartError),
+    /// The file could not be written
+    Io(std::io::Error),
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadError::MissingFilename => write!(f, "The file has no name"),
+            UploadError::ContentType(Some(c)) => write!(f, "Content type {} is not allowed", c),
+            UploadError::ContentType(None) => write!(f, "The file has no content type"),
+            UploadError::TooLarge => write!(f, "The file is too large"),
+            UploadError::Payload(e) => write!(f, "Could not read the file: {}", e),
+            UploadError::Io(e) => write!(f, "Could not write the file: {}", e),
+        }
+    }
+}
+
+impl UploadError {
+    fn response(&self) -> HttpResponse {
+        let (status, code) = match self {
+            UploadError::MissingFilename | UploadError::Payload(_) => {
+                (StatusCode::BAD_REQUEST, ErrorCode::InvalidRequest)
+            }
+            UploadError::ContentType(_) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                ErrorCode::UnsupportedMediaType,
+            ),
+            UploadError::TooLarge => (StatusCode::PAYLOAD_TOO_LARGE, ErrorCode::PayloadTooLarge),
+            UploadError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal),
+        };
+        error_response(status, ErrorResponse::new(code, self.to_string()))
+    }
+}
+
+/// The response to a malformed upload.
+fn invalid_upload(message: String) -> HttpResponse {
+    error_response(
+        StatusCode::BAD_REQUEST,
+        ErrorResponse::new(ErrorCode::InvalidRequest, message),
+    )
+}
+
+/// Save the uploaded file of the `file` part to the workspace of the job, and return its path.
+async fn save_upload(
+    uuid: Uuid,
+    field: &mut Field,
+    config: &UploadConfig,
+) -> Result<PathBuf, UploadError> {
+    let content_type = field.content_type().map(|m| m.essence_str().to_string());
+    if !content_type
+        .as_deref()
+        .is_some_and(|c| config.allows_content_type(c))
+    {
+        return Err(UploadError::ContentType(content_type));
+    }
+
+    // Only keep the name of the file, so it cannot be written outside of the workspace
+    let filename = field
+        .content_disposition()
+        .and_then(|d| d.get_filename())
+        .and_then(|f| Path::new(f).file_name())
+        .map(PathBuf::from)
+        .ok_or(UploadError::MissingFilename)?;
+
+    let path = workspace::upload_path(uuid, filename.as_path());
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(UploadError::Io)?;
+    }
+    let mut file = tokio::fs::File::create(path.as_path())
+        .await
+        .map_err(UploadError::Io)?;
+
+    let max_size = config.max_size_bytes();
+    let mut size: u64 = 0;
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk.map_err(UploadError::Payload)?;
+        size += chunk.len() as u64;
+        if size > max_size {
+            return Err(UploadError::TooLarge);
+        }
+        file.write_all(&chunk).await.map_err(UploadError::Io)?;
+    }
+    file.flush().await.map_err(UploadError::Io)?;
+
+    log::info!("Received upload {:?} of {} bytes", path, size);
+
+    tokio::fs::canonicalize(path.as_path())
+        .await
+        .map_err(UploadError::Io)
+}
+
+/// Read the options of the job from the `options` part.
+async fn read_options(field: &mut Field) -> anyhow::Result<NewJobOptions> {
+    let mut data = Vec::new();
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk.map_err(|e| anyhow::Error::msg(e.to_string()))?;
+        data.extend_from_slice(&chunk);
+        if data.len() > MAX_OPTIONS_SIZE {
+            return Err(anyhow::Error::msg(format!(
+                "The options are larger than {} bytes",
+                MAX_OPTIONS_SIZE
+            )));
+        }
+    }
+
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// Request handler for queueing a job on a file uploaded with the request, as `multipart/form-data`. The `file` part holds the file, and an
+/// optional `options` part holds the options of the job as JSON, the same as `/newJob` takes them.
+#[post("/uploadJob")]
+pub async fn upload_job(
+    req: HttpRequest,
+    mut payload: Multipart,
+    config: web::Data<Arc<Config>>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    // Reject files that are too large before receiving them when the client says how large the request is
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if content_length.is_some_and(|l| l > config.uploads.max_size_bytes() + MAX_OPTIONS_SIZE as u64)
+    {
+        return error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorResponse::new(
+                ErrorCode::PayloadTooLarge,
+                format!(
+                    "The upload is larger than {} MB",
+                    config.uploads.max_size_mb
+                ),
+            ),
+        );
+    }
+
+    let uuid = Uuid::new_v4();
+
+    let workspace_path = match workspace::setup_workspace(uuid).await {
+        Ok(w) => w,
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorResponse::new(
+                    ErrorCode::Internal,
+                    format!("Error creating workspace: {}", e),
+                ),
+            );
+        }
+    };
+
+    let mut options = NewJobOptions::default();
+    let mut source = None;
+
+    while let Some(field) = payload.next().await {
+        let mut field = match field {
+            Ok(f) => f,
+            Err(e) => {
+                workspace::cleanup_workspace(workspace_path).await;
+                return invalid_upload(format!("Could not read upload: {}", e));
+            }
+        };
+
+        match field.name() {
+            Some("file") if source.is_some() => {
+                workspace::cleanup_workspace(workspace_path).await;
+                return invalid_upload(String::from("Uploads must have a single file"));
+            }
+            Some("file") => match save_upload(uuid, &mut field, &config.uploads).await {
+                Ok(path) => source = Some(path),
+                Err(e) => {
+                    workspace::cleanup_workspace(workspace_path).await;
+                    return e.response();
+                }
+            },
+            Some("options") => match read_options(&mut field).await {
+                Ok(o) => options = o,
+                Err(e) => {
+                    workspace::cleanup_workspace(workspace_path).await;
+                    return error_response(
+                        StatusCode::BAD_REQUEST,
+                        ErrorResponse::new(
+                            ErrorCode::InvalidJob,
+                            format!("Invalid job options: {:#}", e),
+                        ),
+                    );
+                }
+            },
+            name => log::warn!("Ignoring unknown part {:?} of upload", name),
+        }
+    }
+
+    let Some(source) = source else {
+        workspace::cleanup_workspace(workspace_path).await;
+        return invalid_upload(String::from("Upload has no file part"));
+    };
+
+    let mut spec =
+        match new_job::build_spec(&req, &options, source, &config, backend.as_ref().as_ref()) {
+            Ok(s) => s,
+            Err(e) => {
+                workspace::cleanup_workspace(workspace_path).await;
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    NewJobError::InvalidJob(e).to_error_response(),
+                );
+            }
+        };
+    spec.upload_retention_secs = Some(config.uploads.retention_secs);
+
+    match new_job::queue_job(uuid, spec, workspace_path, &sch).await {
+        Ok(()) => HttpResponse::Ok().json(NewJobResponse { uuid }),
+        Err(e) => {
+            let status = match e {
+                NewJobError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+                NewJobError::InvalidPath(_) | NewJobError::InvalidJob(_) => StatusCode::BAD_REQUEST,
+                NewJobError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, e.to_error_response())
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use actix_web::{
+    delete, get,
+    http::{header, StatusCode},
+    post, web, HttpRequest, HttpResponse, Responder,
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::batch::{
+    BatchArtifactsQuery, CancelBatchResponse, NewBatchRequest, NewBatchResponse,
+};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::Config,
+    routes::{
+        cancel_batch::cancel_batch_jobs,
+        error::{batch_not_found, error_response},
+        get_batch::batch_response,
+        get_batch_artifacts::{batch_archive, BatchArchiveError},
+        new_batch::create_batch as queue_batch,
+        new_job::NewJobError,
+    },
+    scheduler::Scheduler,
+};
+
+/// Queue a job for every file of a list, or of a directory. Responds with `201` and the batch, `422` if a file cannot be found, no file
+/// matches or the options are invalid, and `503` while the server shuts down.
+#[post("/batches")]
+pub async fn create_batch(
+    req: HttpRequest,
+    json: web::Json<NewBatchRequest>,
+    config: web::Data<Arc<Config>>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match queue_batch(&req, &json, &config, backend.as_ref().as_ref(), &sch).await {
+        Ok((uuid, jobs)) => HttpResponse::Created()
+            .insert_header((header::LOCATION, format!("/v2/batches/{}", uuid)))
+            .json(NewBatchResponse { uuid, jobs }),
+        Err(e) => {
+            let status = match e {
+                NewJobError::InvalidPath(_) | NewJobError::InvalidJob(_) => {
+                    StatusCode::UNPROCESSABLE_ENTITY
+                }
+                NewJobError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+                NewJobError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, e.to_error_response())
+        }
+    }
+}
+
+/// Get the aggregate status of a batch, like `/getBatch` does.
+#[get("/batches/{id}")]
+pub async fn get_batch(
+    path: web::Path<Uuid>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match batch_response(&*sch.lock().await, id) {
+        Some(batch) => HttpResponse::Ok().json(batch),
+        None => error_response(StatusCode::NOT_FOUND, batch_not_found(id)),
+    }
+}
+
+/// Cancel the jobs of a batch that are not finished yet. Responds with the jobs that were canceled.
+#[delete("/batches/{id}")]
+pub async fn delete_batch(
+    path: web::Path<Uuid>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match cancel_batch_jobs(&sch, id).await {
+        Some(canceled) => HttpResponse::Ok().json(CancelBatchResponse { canceled }),
+        None => error_response(StatusCode::NOT_FOUND, batch_not_found(id)),
+    }
+}
+
+/// Download the files the finished jobs of a batch produced, as a ZIP archive. Responds with `404` if no finished job produced a file of
+/// the format asked for.
+#[get("/batches/{id}/artifacts")]
+pub async fn get_batch_artifacts(
+    path: web::Path<Uuid>,
+    query: web::Query<BatchArtifactsQuery>,
+    config: web::Data<Arc<Config>>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match batch_archive(&sch, backend.as_ref().as_ref(), &config, id, query.format).await {
+        Ok(response) => response,
+        Err(e) => {
+            let status = match e {
+                BatchArchiveError::UnknownBatch(_) | BatchArchiveError::NoArtifacts(_) => {
+                    StatusCode::NOT_FOUND
+                }
+                BatchArchiveError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            error_response(status, e.to_error_response())
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use actix_web::{
+    delete, get,
+    http::{header, StatusCode},
+    post, web, Either, HttpRequest, HttpResponse, Responder,
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    error::{ErrorCode, ErrorResponse},
+    job_query::ListJobsRequest,
+    output_format::OutputFormat,
+    GetArtifactsResponse, GetJobEventsRequest, ListJobsResponse, NewJobRequest,
+};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::Config,
+    routes::{
+        error::{error_response, job_not_finished, job_not_found},
+        get_all_statuses, get_artifacts,
+        get_job::{find_job_output, serve_output, JobOutputError},
+        get_job_events::stream_events,
+        get_status::status_response,
+        get_webhook_deliveries::deliveries_response,
+        new_job::{self, NewJobError},
+    },
+    scheduler::Scheduler,
+    workspace,
+};
+
+/// The number of jobs listed at once, unless the request asks for another limit
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Queue a new job. Responds with `201` and the job, `422` if the file cannot be found or the options are invalid, and `503` while the
+/// server shuts down.
+#[post("/jobs")]
+pub async fn create_job(
+    req: HttpRequest,
+    json: web::Json<NewJobRequest>,
+    config: web::Data<Arc<Config>>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let uuid =
+        match new_job::create_job(&req, &json, &config, backend.as_ref().as_ref(), &sch).await {
+            Ok(uuid) => uuid,
+            Err(e) => {
+                let status = match e {
+                    NewJobError::InvalidPath(_) | NewJobError::InvalidJob(_) => {
+                        StatusCode::UNPROCESSABLE_ENTITY
+                    }
+                    NewJobError::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+                    NewJobError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                return error_response(status, e.to_error_response());
+            }
+        };
+
+    match status_response(&*sch.lock().await, uuid) {
+        Some(job) => HttpResponse::Created()
+            .insert_header((header::LOCATION, format!("/v2/jobs/{}", uuid)))
+            .json(job),
+        None => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorResponse::new(
+                ErrorCode::Internal,
+                format!("Job {} could not be found right after it was queued", uuid),
+            ),
+        ),
+    }
+}
+
+/// List the jobs matching the query, a page at a time. Responds with `400` if the query is invalid.
+#[get("/jobs")]
+pub async fn list_jobs(
+    query: web::Query<ListJobsRequest>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match get_all_statuses::list_jobs(&*sch.lock().await, &query, Some(DEFAULT_PAGE_SIZE)) {
+        Ok(page) => HttpResponse::Ok().json(ListJobsResponse {
+            jobs: page.jobs,
+            next_cursor: page.next_cursor,
+        }),
+        Err(e) => error_response(
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new(ErrorCode::InvalidRequest, format!("{:#}", e)),
+        ),
+    }
+}
+
+/// Get a job with its status. Responds with `404` if the job cannot be found.
+#[get("/jobs/{id}")]
+pub async fn get_job(
+    path: web::Path<Uuid>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match status_response(&*sch.lock().await, id) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => error_response(StatusCode::NOT_FOUND, job_not_found(id)),
+    }
+}
+
+/// Cancel a job that is queued, running or waiting to be retried. Responds with `204`, `404` if the job cannot be found, and `409` if it
+/// is already finished.
+#[delete("/jobs/{id}")]
+pub async fn delete_job(
+    path: web::Path<Uuid>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    let mut sch = sch.lock().await;
+
+    let Some(status) = sch.get_job_status(id) else {
+        return error_response(StatusCode::NOT_FOUND, job_not_found(id));
+    };
+
+    if status.is_finished() {
+        return error_response(
+            StatusCode::CONFLICT,
+            ErrorResponse::new(
+                ErrorCode::JobFinished,
+                format!("Job {} is already finished", id),
+            )
+            .with_details(serde_json::json!({ "status": status })),
+        );
+    }
+
+    if let Err(e) = sch.cancel_job(id).await {
+        let status = if e.is_client_error() {
+            StatusCode::NOT_FOUND
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        };
+        return error_response(status, ErrorResponse::new(e.code(), e.to_string()));
+    }
+    drop(sch);
+
+    workspace::cleanup_workspace(workspace::workspace_path(id)).await;
+
+    HttpResponse::NoContent().finish()
+}
+
+/// List the files a finished job produced. Responds with `404` if the job cannot be found, and `409` if it is not finished.
+#[get("/jobs/{id}/artifacts")]
+pub async fn list_artifacts(
+    path: web::Path<Uuid>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    let sch = sch.lock().await;
+
+    let Some(status) = sch.get_job_status(id) else {
+        return error_response(StatusCode::NOT_FOUND, job_not_found(id));
+    };
+
+    if !status.is_finished() {
+        return error_response(StatusCode::CONFLICT, job_not_finished(id));
+    }
+
+    let Some(metadata) = sch.get_job_metadata(id) else {
+        return error_response(StatusCode::NOT_FOUND, job_not_found(id));
+    };
+    let output_formats = metadata.output_formats;
+    let artifacts = get_artifacts::list_artifacts(backend.as_ref().as_ref(), id, output_formats);
+
+    HttpResponse::Ok().json(GetArtifactsResponse { artifacts })
+}
+
+/// Download the file of the given format a finished job produced. Responds with `404` if the job cannot be found or did not produce the
+/// file, and `409` if it is not finished.
+#[get("/jobs/{id}/artifacts/{format}")]
+pub async fn get_artifact(
+    path: web::Path<(Uuid, OutputFormat)>,
+    backend: web::Data<Arc<dyn TranscriptionBackend>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let (id, format) = path.into_inner();
+
+    let output = find_job_output(
+        &*sch.lock().await,
+        backend.as_ref().as_ref(),
+        id,
+        Some(format),
+    );
+
+    match output {
+        Ok((format, file_path)) => serve_output(file_path, format),
+        Err(e) => {
+            let status = match e {
+                JobOutputError::UnknownJob
+                | JobOutputError::FormatNotRequested(_)
+                | JobOutputError::MissingOutput(_) => StatusCode::NOT_FOUND,
+                JobOutputError::NotFinished => StatusCode::CONFLICT,
+                JobOutputError::NoFormats | JobOutputError::MissingWorkspace(_) => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            };
+            Either::Left(error_response(status, e.to_error_response()))
+        }
+    }
+}
+
+/// The attempts at calling the webhooks of a job, oldest first. Responds with `404` if the job is unknown.
+#[get("/jobs/{id}/webhooks")]
+pub async fn webhook_deliveries(
+    path: web::Path<Uuid>,
+    config: web::Data<Arc<Config>>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match deliveries_response(sch.get_ref(), config.get_ref(), id).await {
+        Some(response) => response,
+        None => error_response(StatusCode::NOT_FOUND, job_not_found(id)),
+    }
+}
+
+/// Follow the lifecycle of a job as server-sent events, like `/getJobEvents` does. Responds with `404` if the job cannot be found.
+#[get("/jobs/{id}/events")]
+pub async fn job_events(
+    path: web::Path<Uuid>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match stream_events(sch.get_ref(), Some(id)).await {
+        Some(response) => response,
+        None => error_response(StatusCode::NOT_FOUND, job_not_found(id)),
+    }
+}
+
+/// Follow the lifecycle of every job as server-sent events, or of the job given in the query. Responds with `404` if that job cannot be
+/// found.
+#[get("/events")]
+pub async fn all_job_events(
+    query: web::Query<GetJobEventsRequest>,
+    sch: web::Data<Arc<Mutex<Scheduler>>>,
+) -> impl Responder {
+    match stream_events(sch.get_ref(), query.uuid).await {
+        Some(response) => response,
+        None => error_response(
+            StatusCode::NOT_FOUND,
+            job_not_found(query.uuid.unwrap_or_default()),
+        ),
+    }
+}
@@ -0,0 +1,25 @@
+//! Resource-oriented API served under `/v2`, answering with status codes that tell clients what went wrong. The v1 routes stay as they were
+//! for existing clients.
+
+use actix_web::{web, Scope};
+
+pub mod batches;
+pub mod jobs;
+
+/// All the routes of the v2 API.
+pub fn scope() -> Scope {
+    web::scope("/v2")
+        .service(jobs::create_job)
+        .service(jobs::list_jobs)
+        .service(jobs::get_job)
+        .service(jobs::delete_job)
+        .service(jobs::list_artifacts)
+        .service(jobs::get_artifact)
+        .service(jobs::job_events)
+        .service(jobs::webhook_deliveries)
+        .service(jobs::all_job_events)
+        .service(batches::create_batch)
+        .service(batches::get_batch)
+        .service(batches::delete_batch)
+        .service(batches::get_batch_artifacts)
+}
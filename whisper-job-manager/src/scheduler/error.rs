@@ -0,0 +1,44 @@
+use std::fmt;
+
+use uuid::Uuid;
+use whisper_job_manager_models::error::ErrorCode;
+
+/// Why the scheduler could not do what it was asked.
+#[derive(Debug)]
+pub enum SchedulerError {
+    /// The job cannot be found
+    JobNotFound(Uuid),
+    /// The status of the job says it is queued, but the job is not in the queue
+    NotQueued(Uuid),
+}
+
+impl SchedulerError {
+    /// Whether the error comes from what the client asked for, rather than from the server.
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            SchedulerError::JobNotFound(_) => true,
+            SchedulerError::NotQueued(_) => false,
+        }
+    }
+
+    /// The code of the error sent to clients.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SchedulerError::JobNotFound(_) => ErrorCode::JobNotFound,
+            SchedulerError::NotQueued(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+impl fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerError::JobNotFound(id) => write!(f, "Job {} could not be found", id),
+            SchedulerError::NotQueued(id) => {
+                write!(f, "Job {} is marked as queued but is not in the queue", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchedulerError {}
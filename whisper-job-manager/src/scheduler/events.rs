@@ -0,0 +1,66 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::{mpsc::UnboundedReceiver, Mutex};
+use uuid::Uuid;
+
+use crate::config::SchedulerConfig;
+
+use super::{run_details::RunDetails, runner::JobOutcome, Scheduler};
+
+/// Something that happened which the scheduler should react to.
+#[derive(Debug)]
+pub enum SchedulerEvent {
+    /// A new job was queued
+    JobQueued(Uuid),
+    /// A job was canceled
+    JobCanceled(Uuid),
+    /// The source of a running job was prepared, and its transcription started
+    PreprocessingFinished(Uuid),
+    /// A running job transcribed more of its file
+    ProgressReported(Uuid),
+    /// The process of a running job ended, reporting the given details in its output
+    JobExited {
+        id: Uuid,
+        outcome: JobOutcome,
+        details: RunDetails,
+    },
+    /// A job that failed is due to be retried
+    RetryDue(Uuid),
+    /// A queued job may have waited for longer than its maximum queue time
+    QueueTimeExceeded(Uuid),
+    /// The scheduler configuration was reloaded
+    ConfigChanged(SchedulerConfig),
+    /// Periodic tick, as a safety net in case an event was missed
+    Tick,
+}
+
+/// Run the scheduler whenever an event arrives, or at least once every tick period.
+pub async fn run_event_loop(
+    scheduler: Arc<Mutex<Scheduler>>,
+    mut events: UnboundedReceiver<SchedulerEvent>,
+    config: SchedulerConfig,
+) {
+    let mut tick = tokio::time::interval(Duration::from_millis(config.tick_millis));
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => match event {
+                Some(e) => e,
+                None => {
+                    log::info!("All scheduler event senders are gone, stopping event loop");
+                    break;
+                }
+            },
+            _ = tick.tick() => SchedulerEvent::Tick,
+        };
+
+        if let SchedulerEvent::ConfigChanged(config) = &event {
+            log::info!("Scheduler configuration changed: {:?}", config);
+            tick = tokio::time::interval(Duration::from_millis(config.tick_millis));
+            // The first tick of an interval completes immediately, which is not needed right after handling an event
+            tick.reset();
+        }
+
+        scheduler.lock().await.handle_event(event).await;
+    }
+}
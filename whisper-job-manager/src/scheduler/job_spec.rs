@@ -0,0 +1,145 @@
+use std::{path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use whisper_job_manager_models::{output_format::OutputFormat, task::Task};
+
+use crate::config::{PreprocessingParams, RetryConfig};
+
+/// Everything needed to run a job. Unlike a `Command`, a job spec can be persisted and is only turned into a command
+/// by the transcription backend once the job is scheduled to run.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobSpec {
+    /// The canonical path of the file to transcribe
+    pub source: PathBuf,
+    /// The language spoken in the file. If not set, whisper detects the language.
+    #[serde(default = "default_language")]
+    pub language: Option<String>,
+    /// Whether whisper transcribes or translates the speech
+    #[serde(default)]
+    pub task: Task,
+    /// The formats of the files whisper writes, without `All`
+    #[serde(default = "default_output_formats")]
+    pub output_formats: Vec<OutputFormat>,
+    /// The model to run with, as asked by the job or set by the scheduler strategy
+    pub model: Option<String>,
+    /// The device to run on, set by the scheduler strategy. If not set, whisper chooses the device.
+    pub device: Option<String>,
+    /// The number of CPU threads whisper uses, set by the scheduler strategy. If not set, whisper chooses the number of threads.
+    #[serde(default)]
+    pub threads: Option<u32>,
+    /// The priority the job was queued with, higher runs first with strategies that use priorities
+    #[serde(default)]
+    pub priority: i32,
+    /// Who submitted the job, as given in the request or the address of the client otherwise
+    #[serde(default)]
+    pub submitter: String,
+    /// How the job is retried when it fails
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// How long the job can run, in seconds
+    #[serde(default)]
+    pub max_runtime_secs: Option<u64>,
+    /// How long the job can wait in the queue before its first attempt, in seconds
+    #[serde(default)]
+    pub max_queue_time_secs: Option<u64>,
+    /// When the job was queued
+    #[serde(default = "chrono::offset::Utc::now")]
+    pub queued_at: DateTime<Utc>,
+    /// If the source was uploaded, how long it is kept once the job is finished, in seconds
+    #[serde(default)]
+    pub upload_retention_secs: Option<u64>,
+    /// How the source is prepared with ffmpeg before it is transcribed. If not set, the source is transcribed as is.
+    #[serde(default)]
+    pub preprocessing: Option<PreprocessingParams>,
+    /// Where the part of the source to transcribe starts, in seconds. Only used with preprocessing.
+    #[serde(default)]
+    pub start_secs: Option<f64>,
+    /// Where the part of the source to transcribe ends, in seconds. Only used with preprocessing.
+    #[serde(default)]
+    pub end_secs: Option<f64>,
+    /// The labels the job was queued with
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The URL the outcome of the job is POSTed to once it finishes, besides the webhooks of the server
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    /// The batch the job was queued with, if any
+    #[serde(default)]
+    pub batch_id: Option<Uuid>,
+}
+
+impl JobSpec {
+    /// Create a spec for transcribing the given file, leaving the rest to the scheduler strategy.
+    pub fn new(source: PathBuf) -> Self {
+        JobSpec {
+            source,
+            language: default_language(),
+            task: Task::default(),
+            output_formats: default_output_formats(),
+            model: None,
+            device: None,
+            threads: None,
+            priority: 0,
+            submitter: String::new(),
+            retry: RetryConfig::default(),
+            max_runtime_secs: None,
+            max_queue_time_secs: None,
+            queued_at: chrono::offset::Utc::now(),
+            upload_retention_secs: None,
+            preprocessing: None,
+            start_secs: None,
+            end_secs: None,
+            tags: Vec::new(),
+            callback_url: None,
+            batch_id: None,
+        }
+    }
+
+    /// How long the job can run, if there is a limit.
+    pub fn max_runtime(&self) -> Option<Duration> {
+        self.max_runtime_secs.map(Duration::from_secs)
+    }
+
+    /// When the uploaded source of the job can be removed, given when the job finished. `None` if the source was not uploaded.
+    pub fn upload_expiry(&self, finished_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let retention =
+            chrono::Duration::from_std(Duration::from_secs(self.upload_retention_secs?)).ok()?;
+        finished_at.checked_add_signed(retention)
+    }
+
+    /// How long the part of the source to transcribe lasts, given the duration of the whole source, in seconds.
+    pub fn transcribed_duration(&self, duration_secs: f64) -> f64 {
+        let end = self
+            .end_secs
+            .map_or(duration_secs, |e| e.min(duration_secs));
+        (end - self.start_secs.unwrap_or(0.0)).max(0.0)
+    }
+
+    /// How far the timestamps of the outputs are shifted, in seconds, when the job only transcribes part of its source. `None` if they are
+    /// not shifted.
+    pub fn output_offset_secs(&self) -> Option<f64> {
+        match self.start_secs {
+            Some(s) if self.preprocessing.is_some() && s > 0.0 => Some(s),
+            _ => None,
+        }
+    }
+
+    /// When the job expires if it has not started yet, if there is a limit.
+    pub fn queue_deadline(&self) -> Option<DateTime<Utc>> {
+        let max_queue_time =
+            chrono::Duration::from_std(Duration::from_secs(self.max_queue_time_secs?)).ok()?;
+        self.queued_at.checked_add_signed(max_queue_time)
+    }
+}
+
+/// Jobs used to always run in French, so that is the language of specs persisted without one
+fn default_language() -> Option<String> {
+    Some(String::from("fr"))
+}
+
+/// Jobs used to only produce SRT files, so that is the format of specs persisted without one
+fn default_output_formats() -> Vec<OutputFormat> {
+    vec![OutputFormat::Srt]
+}
@@ -1,68 +1,248 @@
-use std::collections::{HashMap, VecDeque};
-
-use tokio::process::{Child, Command};
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{broadcast, mpsc::UnboundedSender};
 use uuid::Uuid;
 
-use anyhow::{Error, Result};
-use whisper_job_manager_models::{job_metadata::JobMetadata, job_status::JobStatus};
-
-use self::strategy::{SchedulerStrategy, SimpleSchedulerStrategy};
-
+use whisper_job_manager_models::{
+    job_event::{JobEvent, JobEventKind},
+    job_metadata::JobMetadata,
+    job_status::JobStatus,
+    retry_policy::FailureKind,
+};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::InterruptedJobPolicy,
+    recovery,
+    store::{JobRecord, JobStoreWriter},
+    webhooks::FinishedJob,
+};
+
+use self::{
+    error::SchedulerError,
+    events::SchedulerEvent,
+    job_spec::JobSpec,
+    progress::Progress,
+    run_details::RunDetails,
+    runner::{JobOutcome, RunningJob},
+    strategy::SchedulerStrategy,
+};
+
+pub mod error;
+pub mod events;
+pub mod job_spec;
+pub mod progress;
+pub mod run_details;
+pub mod runner;
 pub mod strategy;
 
 const DEFAULT_CAPACTITY: usize = 32;
 
+/// How many job events are kept for clients that are slow to read them. Slower clients miss events.
+const JOB_EVENTS_CAPACITY: usize = 256;
+
 #[derive(Debug)]
 pub struct Scheduler {
     job_metadata: HashMap<Uuid, JobMetadata>,
     job_statuses: HashMap<Uuid, JobStatus>,
-    running_jobs: HashMap<Uuid, Child>,
-    queued_commands: VecDeque<(Uuid, Command)>,
+    job_specs: HashMap<Uuid, JobSpec>,
+    running_jobs: HashMap<Uuid, RunningJob>,
+    queued_jobs: VecDeque<(Uuid, JobSpec)>,
+    retrying_jobs: HashMap<Uuid, DateTime<Utc>>,
     strategy: Box<dyn SchedulerStrategy>,
+    backend: Arc<dyn TranscriptionBackend>,
+    store: JobStoreWriter,
+    events: UnboundedSender<SchedulerEvent>,
+    /// Whether the server is shutting down, in which case no new job is accepted or started
+    draining: bool,
+    /// Where changes to jobs are published for clients following them
+    job_events: broadcast::Sender<JobEvent>,
+    /// Where jobs are sent once they finish, to call their webhooks
+    finished_jobs: UnboundedSender<FinishedJob>,
 }
 
-impl Default for Scheduler {
-    fn default() -> Self {
-        Self {
+impl Scheduler {
+    /// Create a scheduler using the given strategy and backend that persists jobs to the given store, re-hydrating it with the records loaded from the store.
+    /// Queued jobs are queued again in order of creation. The records are expected to be reconciled already, see
+    /// `recovery::recover_jobs`. Events are sent to `events` whenever the scheduler should run again, and are expected to be
+    /// handled by `events::run_event_loop`. Jobs are sent to `finished_jobs` once they finish, and are expected to be
+    /// handled by `webhooks::run_dispatcher`.
+    pub fn new(
+        store: JobStoreWriter,
+        records: Vec<JobRecord>,
+        strategy: Box<dyn SchedulerStrategy>,
+        backend: Arc<dyn TranscriptionBackend>,
+        events: UnboundedSender<SchedulerEvent>,
+        finished_jobs: UnboundedSender<FinishedJob>,
+    ) -> Self {
+        let mut scheduler = Self {
             job_metadata: HashMap::with_capacity(DEFAULT_CAPACTITY),
             job_statuses: HashMap::with_capacity(DEFAULT_CAPACTITY),
+            job_specs: HashMap::with_capacity(DEFAULT_CAPACTITY),
             running_jobs: HashMap::with_capacity(DEFAULT_CAPACTITY),
-            queued_commands: VecDeque::with_capacity(DEFAULT_CAPACTITY),
-            strategy: Box::new(SimpleSchedulerStrategy::default()),
+            queued_jobs: VecDeque::with_capacity(DEFAULT_CAPACTITY),
+            retrying_jobs: HashMap::with_capacity(DEFAULT_CAPACTITY),
+            strategy,
+            backend,
+            store,
+            events,
+            draining: false,
+            job_events: broadcast::channel(JOB_EVENTS_CAPACITY).0,
+            finished_jobs,
+        };
+
+        for record in records {
+            let id = record.id;
+
+            match record.status {
+                JobStatus::Queued => {
+                    scheduler.schedule_queue_expiry(id, &record.spec);
+                    scheduler.queued_jobs.push_back((id, record.spec.clone()));
+                }
+                JobStatus::Retrying { next_retry_at, .. } => {
+                    scheduler.schedule_retry(id, next_retry_at)
+                }
+                _ => {}
+            }
+
+            scheduler.job_statuses.insert(id, record.status);
+            scheduler.job_metadata.insert(id, record.metadata);
+            scheduler.job_specs.insert(id, record.spec);
         }
+
+        log::info!(
+            "Restored {} jobs, {} of them queued and {} waiting to be retried",
+            scheduler.job_statuses.len(),
+            scheduler.queued_jobs.len(),
+            scheduler.retrying_jobs.len()
+        );
+
+        scheduler
     }
-}
 
-impl Scheduler {
-    /// Perform one run of the scheduler. This will do the following:
-    /// * Clean up any finished runs
-    /// * Attempt to start running jobs in the queue
-    ///
-    /// This will also update the status of jobs that are cleared or being running
-    pub async fn run(&mut self) {
-        log::info!("Starting scheduler run: {:?}", self);
-
-        // Clean up finished runs
-        let num_cleaned_runs = self.remove_finished_jobs().await;
-        log::info!("Cleared {} finished runs", num_cleaned_runs);
-
-        // Add new runs
+    /// React to an event, then attempt to start running jobs in the queue. This will also update the status of jobs
+    /// that ended or started running.
+    pub async fn handle_event(&mut self, event: SchedulerEvent) {
+        log::debug!("Handling scheduler event {:?}", event);
+
+        match event {
+            SchedulerEvent::JobExited {
+                id,
+                outcome,
+                details,
+            } => self.record_job_exit(id, outcome, details),
+            SchedulerEvent::Tick => {
+                let num_orphaned_jobs = self.remove_orphaned_jobs();
+                if num_orphaned_jobs > 0 {
+                    log::warn!(
+                        "Cleared {} jobs whose exit was never reported",
+                        num_orphaned_jobs
+                    );
+                }
+                self.requeue_due_retries();
+                self.expire_queued_jobs();
+                self.remove_expired_uploads();
+            }
+            SchedulerEvent::QueueTimeExceeded(id) => {
+                log::debug!("Job {} may have waited too long in the queue", id);
+                self.expire_queued_jobs();
+            }
+            SchedulerEvent::RetryDue(id) => {
+                log::debug!("Job {} is due to be retried", id);
+                self.requeue_due_retries();
+            }
+            SchedulerEvent::JobQueued(id) => log::debug!("Job {} was queued", id),
+            SchedulerEvent::JobCanceled(id) => log::debug!("Job {} was canceled", id),
+            SchedulerEvent::PreprocessingFinished(id) => self.record_preprocessing_end(id),
+            SchedulerEvent::ProgressReported(id) => self.record_job_progress(id),
+            SchedulerEvent::ConfigChanged(config) => match strategy::build_strategy(&config) {
+                Ok(s) => {
+                    log::info!("Switching to scheduler strategy {:?}", s);
+                    self.strategy = s;
+                }
+                Err(e) => log::error!(
+                    "Invalid scheduler strategy configuration, keeping {:?}: {:#}",
+                    self.strategy,
+                    e
+                ),
+            },
+        }
+
         let num_new_jobs = self.run_queued_jobs();
-        log::info!("Started running {:?} new jobs", num_new_jobs);
+        if num_new_jobs > 0 {
+            log::info!("Started running {:?} new jobs: {:?}", num_new_jobs, self);
+        }
+    }
+
+    /// Stop accepting and starting new jobs, letting the running jobs finish.
+    pub fn start_draining(&mut self) {
+        log::info!(
+            "Draining scheduler, {} jobs are still running and {} are queued",
+            self.running_jobs.len(),
+            self.queued_jobs.len()
+        );
+        self.draining = true;
+    }
+
+    /// Whether the scheduler stopped accepting new jobs.
+    pub fn is_draining(&self) -> bool {
+        self.draining
+    }
+
+    /// The number of jobs whose process is running.
+    pub fn running_job_count(&self) -> usize {
+        self.running_jobs.len()
+    }
+
+    /// Stop the processes of the jobs that are still running, and record their status according to the given policy. Queued jobs are left in
+    /// the job store, so they run again on the next start.
+    pub async fn shutdown(&mut self, policy: InterruptedJobPolicy) {
+        self.draining = true;
+
+        let running_jobs: Vec<(Uuid, RunningJob)> = self.running_jobs.drain().collect();
+        for (id, running_job) in running_jobs {
+            log::info!("Stopping job {} for shutdown", id);
+            running_job.kill().await;
+
+            let status = match policy {
+                InterruptedJobPolicy::Fail => JobStatus::Failed {
+                    reason: Some(String::from("Server shut down while the job was running")),
+                },
+                InterruptedJobPolicy::Requeue => JobStatus::Queued,
+            };
+            self.set_job_status(id, status);
+            self.update_job_metadata(id);
+        }
 
-        log::info!("Finished running scheduler: {:?}", self);
+        // The statuses have to be on disk before the server stops
+        self.store.flush().await;
+
+        log::info!(
+            "Scheduler shut down, {} jobs are left in the queue",
+            self.queued_jobs.len()
+        );
     }
 
     /// Queue a new job, which will be scheduled to run in the future. Update the status of the new job accordingly.
-    pub fn queue_new_job(&mut self, job: (Uuid, Command), metadata: JobMetadata) {
+    pub fn queue_new_job(&mut self, job: (Uuid, JobSpec), metadata: JobMetadata) {
         log::debug!("Queueing new job {:?}: {:?}", job, self);
-        self.job_statuses.insert(job.0, JobStatus::Queued);
+        self.set_job_status(job.0, JobStatus::Queued);
         self.job_metadata.insert(job.0, metadata);
-        self.queued_commands.push_back(job);
+        self.job_specs.insert(job.0, job.1.clone());
+        self.persist_job(job.0);
+        self.schedule_queue_expiry(job.0, &job.1);
+        let id = job.0;
+        self.queued_jobs.push_back(job);
+        self.notify(SchedulerEvent::JobQueued(id));
     }
 
-    /// Cancel a job, either one that is running or one that is queued. Update the status accordingly.
-    pub async fn cancel_job(&mut self, id: Uuid) -> Result<()> {
+    /// Cancel a job, either one that is running, queued or waiting to be retried. Update the status accordingly.
+    pub async fn cancel_job(&mut self, id: Uuid) -> Result<(), SchedulerError> {
         // If the job was already finished, just ignore
         let current_status = self.get_job_status(id);
         if let Some(s) = current_status {
@@ -75,83 +255,361 @@ impl Scheduler {
                 return Ok(());
             }
         } else {
-            let msg = format!("Status for job with ID {} not found", id);
-            return Err(Error::msg(msg));
+            return Err(SchedulerError::JobNotFound(id));
         }
 
         // Check the running jobs first
-        if self.running_jobs.contains_key(&id) {
-            let mut child_handle = self.running_jobs.remove(&id).unwrap();
+        if let Some(running_job) = self.running_jobs.remove(&id) {
+            running_job.kill().await;
+        } else if self.retrying_jobs.remove(&id).is_some() {
+            log::debug!("Job {} will not be retried", id);
+        } else {
+            // Otherwise check the queued jobs
+            self.cancel_queued_job(id)?;
+        }
+
+        // Update the status
+        self.set_job_status(id, JobStatus::Canceled);
+        self.update_job_metadata(id);
+        self.notify(SchedulerEvent::JobCanceled(id));
 
-            if let Err(e) = child_handle.kill().await {
-                let msg = format!("Failed to kill child process {:?}: {}", child_handle, e);
-                return Err(Error::msg(msg));
+        Ok(())
+    }
+
+    /// Cancel every job of a batch that is not finished yet, and return the jobs that were canceled.
+    pub async fn cancel_batch(&mut self, batch: Uuid) -> Vec<Uuid> {
+        let mut canceled = vec![];
+
+        for id in self.get_batch_jobs(batch) {
+            if self.get_job_status(id).is_some_and(|s| s.is_finished()) {
+                continue;
             }
 
-            return Ok(());
+            match self.cancel_job(id).await {
+                Ok(()) => canceled.push(id),
+                Err(e) => log::error!("Could not cancel job {} of batch {}: {}", id, batch, e),
+            }
         }
 
-        // Otherwise check the queued jobs
-        self.cancel_queued_job(id)?;
+        canceled
+    }
 
-        // Update the status
-        self.job_statuses.insert(id, JobStatus::Canceled);
+    /// Record the end of the process of a running job. Jobs that are not running anymore, e.g. because they were canceled,
+    /// are ignored.
+    fn record_job_exit(&mut self, id: Uuid, outcome: JobOutcome, details: RunDetails) {
+        if self.running_jobs.remove(&id).is_none() {
+            log::debug!("Job {} is not running anymore, ignoring its exit", id);
+            return;
+        }
+
+        if let Some(m) = self.job_metadata.get_mut(&id) {
+            log::debug!("Job {} reported {:?}", id, details);
+            if details.detected_language.is_some() {
+                m.detected_language = details.detected_language;
+            }
+            if details.model.is_some() {
+                m.model = details.model;
+            }
+            if details.device.is_some() {
+                m.device = details.device;
+            }
+        }
+
+        let status = match outcome {
+            JobOutcome::Exited(e) if e.success() => JobStatus::Succeeded,
+            // No exit code means the process was killed by a signal
+            JobOutcome::Exited(e) if e.code().is_none() => {
+                return self.record_job_failure(
+                    id,
+                    FailureKind::Killed,
+                    format!("Process was killed: {}", e),
+                )
+            }
+            JobOutcome::Exited(e) => {
+                return self.record_job_failure(
+                    id,
+                    FailureKind::NonZeroExit,
+                    format!("Reported exit code {:?}", e.code()),
+                )
+            }
+            JobOutcome::Killed => JobStatus::Canceled,
+            JobOutcome::TimedOut(max_runtime) => JobStatus::Failed {
+                reason: Some(format!(
+                    "Timed out after running for longer than {} seconds",
+                    max_runtime.as_secs()
+                )),
+            },
+            JobOutcome::WaitFailed(e) => {
+                return self.record_job_failure(
+                    id,
+                    FailureKind::Lost,
+                    format!("Process exit was never recorded: {}", e),
+                )
+            }
+            JobOutcome::StartFailed(e) => {
+                return self.record_job_failure(
+                    id,
+                    FailureKind::StartFailed,
+                    format!("Failed to start process: {}", e),
+                )
+            }
+            JobOutcome::PreprocessingFailed(e) => {
+                return self.record_job_failure(
+                    id,
+                    FailureKind::NonZeroExit,
+                    format!("Preprocessing failed: {}", e),
+                )
+            }
+            JobOutcome::ShiftFailed(e) => {
+                // Some outputs may be shifted and others not, so none of them can be served
+                if let Some(spec) = self.job_specs.get(&id) {
+                    recovery::remove_outputs(id, spec, self.backend.as_ref());
+                }
+
+                JobStatus::Failed {
+                    reason: Some(format!(
+                        "Could not shift the timestamps of the outputs: {}",
+                        e
+                    )),
+                }
+            }
+        };
+
+        log::info!("Job {} finished with status {:?}", id, status);
+
+        self.set_job_status(id, status);
         self.update_job_metadata(id);
+    }
 
-        Ok(())
+    /// Record that the source of a running job was prepared, and that its transcription started.
+    fn record_preprocessing_end(&mut self, id: Uuid) {
+        if !self.running_jobs.contains_key(&id) {
+            log::debug!(
+                "Job {} is not running anymore, ignoring its preprocessing",
+                id
+            );
+            return;
+        }
+
+        log::info!("Job {} was preprocessed, transcribing it", id);
+        self.set_job_status(id, JobStatus::Running);
+        self.update_job_metadata(id);
     }
 
-    /// Remove jobs whose process is reported to be finished. Update the status of jobs accordingly.
-    pub async fn remove_finished_jobs(&mut self) -> usize {
-        let mut jobs_to_remove = vec![];
-
-        for job in self.running_jobs.iter_mut() {
-            let exit_status = job.1.try_wait();
-            match exit_status {
-                Ok(exit_status) => {
-                    // If there is an exit status, check if it is success or failure and update list accordingly
-                    if let Some(e) = exit_status {
-                        let status = if e.success() {
-                            JobStatus::Succeeded
-                        } else {
-                            JobStatus::Failed {
-                                reason: Some(format!("Reported exit code {:?}", e.code())),
-                            }
-                        };
-
-                        jobs_to_remove.push((*job.0, status));
-                    }
+    /// Record a failed attempt of a job. The job waits to be retried if its retry policy allows it, and is marked as failed otherwise.
+    fn record_job_failure(&mut self, id: Uuid, kind: FailureKind, reason: String) {
+        let attempts = self
+            .job_metadata
+            .get(&id)
+            .map(|m| m.attempts)
+            .unwrap_or_default();
+
+        // Jobs whose retry is too far away for its time to be represented fail instead
+        let next_retry_at = self
+            .job_specs
+            .get(&id)
+            .map(|spec| &spec.retry)
+            .filter(|retry| retry.should_retry(kind, attempts))
+            .and_then(|retry| chrono::Duration::from_std(retry.backoff(attempts)).ok())
+            .and_then(|backoff| chrono::offset::Utc::now().checked_add_signed(backoff));
+
+        let status = match next_retry_at {
+            Some(next_retry_at) => {
+                if let Some(m) = self.job_metadata.get_mut(&id) {
+                    m.next_retry_at = Some(next_retry_at);
                 }
-                Err(e) => {
-                    // Otherwise, attempt to kill the process and report that the process failed
-                    log::warn!("Could not find the exit status of job {:?}, attempting to killing process: {}", job, e);
-                    if let Err(kill_e) = job.1.kill().await {
-                        log::error!("Could not kill job {:?}: {}", job, kill_e);
-                        continue;
-                    }
-                    jobs_to_remove.push((
-                        *job.0,
-                        JobStatus::Failed {
-                            reason: Some(String::from("Process exit was never recorded")),
-                        },
-                    ));
+                self.schedule_retry(id, next_retry_at);
+
+                JobStatus::Retrying {
+                    attempt: attempts,
+                    next_retry_at,
+                    reason: Some(reason),
                 }
             }
+            _ if attempts > 1 => JobStatus::Failed {
+                reason: Some(format!("{} (after {} attempts)", reason, attempts)),
+            },
+            _ => JobStatus::Failed {
+                reason: Some(reason),
+            },
+        };
+
+        log::info!("Job {} failed ({:?}) with status {:?}", id, kind, status);
+
+        self.set_job_status(id, status);
+        self.update_job_metadata(id);
+    }
+
+    /// Make the job wait until the given time, after which it is queued again.
+    fn schedule_retry(&mut self, id: Uuid, next_retry_at: DateTime<Utc>) {
+        self.retrying_jobs.insert(id, next_retry_at);
+
+        let events = self.events.clone();
+        let delay = (next_retry_at - chrono::offset::Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            // An error means the scheduler is gone, so there is nothing left to retry
+            let _ = events.send(SchedulerEvent::RetryDue(id));
+        });
+    }
+
+    /// Expire the job once its maximum queue time has passed, if it has one.
+    fn schedule_queue_expiry(&self, id: Uuid, spec: &JobSpec) {
+        let Some(deadline) = spec.queue_deadline() else {
+            return;
+        };
+
+        let events = self.events.clone();
+        let delay = (deadline - chrono::offset::Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            // An error means the scheduler is gone, so there is nothing left to expire
+            let _ = events.send(SchedulerEvent::QueueTimeExceeded(id));
+        });
+    }
+
+    /// Remove the queued jobs that have not started yet and have waited for longer than their maximum queue time, marking them as expired.
+    /// Jobs queued again to be retried already started once, so they do not expire.
+    fn expire_queued_jobs(&mut self) {
+        let now = chrono::offset::Utc::now();
+        let expired_jobs: Vec<Uuid> = self
+            .queued_jobs
+            .iter()
+            .filter(|(id, spec)| {
+                let never_started = self.job_metadata.get(id).is_some_and(|m| m.attempts == 0);
+                never_started && spec.queue_deadline().is_some_and(|d| d <= now)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        if expired_jobs.is_empty() {
+            return;
+        }
+
+        self.queued_jobs
+            .retain(|(id, _)| !expired_jobs.contains(id));
+
+        for id in expired_jobs {
+            log::info!("Job {} waited too long in the queue, expiring it", id);
+            self.set_job_status(id, JobStatus::Expired);
+            self.update_job_metadata(id);
+        }
+    }
+
+    /// Queue the jobs whose retry is due again.
+    fn requeue_due_retries(&mut self) {
+        let now = chrono::offset::Utc::now();
+        let due_jobs: Vec<Uuid> = self
+            .retrying_jobs
+            .iter()
+            .filter(|(_, next_retry_at)| **next_retry_at <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in due_jobs {
+            self.retrying_jobs.remove(&id);
+
+            let Some(spec) = self.job_specs.get(&id).cloned() else {
+                log::warn!("Cannot retry job {}, its spec is not known", id);
+                continue;
+            };
+
+            log::info!("Queueing job {} again to retry it", id);
+
+            if let Some(m) = self.job_metadata.get_mut(&id) {
+                m.next_retry_at = None;
+            }
+            self.set_job_status(id, JobStatus::Queued);
+            self.update_job_metadata(id);
+            self.queued_jobs.push_back((id, spec));
         }
+    }
 
-        let removed_jobs_count = jobs_to_remove.len();
+    /// Remove the uploaded sources of finished jobs that were kept for long enough.
+    fn remove_expired_uploads(&self) {
+        let now = chrono::offset::Utc::now();
 
-        for (job_id, job_status) in jobs_to_remove {
-            self.running_jobs.remove(&job_id);
-            self.job_statuses.insert(job_id, job_status);
-            self.update_job_metadata(job_id);
+        for (id, spec) in self.job_specs.iter() {
+            let finished = self.job_statuses.get(id).is_some_and(|s| s.is_finished());
+            let expired = self
+                .job_metadata
+                .get(id)
+                .and_then(|m| spec.upload_expiry(m.updated_at))
+                .is_some_and(|e| e <= now);
+
+            if !finished || !expired || !spec.source.exists() {
+                continue;
+            }
+
+            match std::fs::remove_file(spec.source.as_path()) {
+                Ok(()) => log::info!("Removed uploaded file {:?} of job {}", spec.source, id),
+                Err(e) => log::error!("Failed to remove uploaded file {:?}: {}", spec.source, e),
+            }
         }
+    }
 
-        removed_jobs_count
+    /// Remove running jobs whose watching task ended without reporting the exit of the process, and report how many
+    /// were removed. This should never happen, and only serves as a safety net.
+    fn remove_orphaned_jobs(&mut self) -> usize {
+        let orphaned_jobs: Vec<Uuid> = self
+            .running_jobs
+            .iter()
+            .filter(|(_, job)| job.is_finished())
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in orphaned_jobs.iter() {
+            self.running_jobs.remove(id);
+            self.record_job_failure(
+                *id,
+                FailureKind::Lost,
+                String::from("Process exit was never recorded"),
+            );
+        }
+
+        orphaned_jobs.len()
     }
 
-    /// Get the status of job with the given UUID. The status of jobs is updated every scheduler run, so this will only be up-to-date
-    /// after a run.
+    /// Follow the changes to jobs, starting from now.
+    pub fn subscribe_to_job_events(&self) -> broadcast::Receiver<JobEvent> {
+        self.job_events.subscribe()
+    }
+
+    /// Change the status of a job, and publish the change to clients following the job.
+    fn set_job_status(&mut self, id: Uuid, status: JobStatus) {
+        self.job_statuses.insert(id, status.clone());
+        self.publish_job_event(JobEvent::status_changed(id, status));
+    }
+
+    /// Publish the progress of a running job to clients following the job.
+    fn record_job_progress(&self, id: Uuid) {
+        let Some(status) = self.get_job_status(id) else {
+            return;
+        };
+        let Some(progress) = self.get_job_progress(id) else {
+            return;
+        };
+
+        self.publish_job_event(JobEvent {
+            uuid: id,
+            kind: JobEventKind::Progress,
+            status,
+            progress: Some(progress.percent),
+            eta: progress.eta_secs,
+            at: chrono::offset::Utc::now(),
+        });
+    }
+
+    fn publish_job_event(&self, event: JobEvent) {
+        // An error only means nobody follows jobs right now
+        let _ = self.job_events.send(event);
+    }
+
+    /// Get the status of job with the given UUID.
     pub fn get_job_status(&self, uuid: Uuid) -> Option<JobStatus> {
         self.job_statuses.get(&uuid).cloned()
     }
@@ -166,13 +624,53 @@ impl Scheduler {
         self.job_metadata.get(&uuid).cloned()
     }
 
+    /// Get the jobs of the batch with the given UUID, oldest first. A batch no job belongs to has no jobs.
+    pub fn get_batch_jobs(&self, batch: Uuid) -> Vec<Uuid> {
+        let mut jobs: Vec<(&Uuid, &JobMetadata)> = self
+            .job_metadata
+            .iter()
+            .filter(|(_, m)| m.batch_id == Some(batch))
+            .collect();
+        jobs.sort_by_key(|(id, m)| (m.created_at, **id));
+        jobs.into_iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Get the file the job associated with the given UUID transcribes.
+    pub fn get_job_source(&self, uuid: Uuid) -> Option<PathBuf> {
+        self.job_specs.get(&uuid).map(|spec| spec.source.clone())
+    }
+
+    /// Get the effective priority of the job associated with the given UUID. The priority of queued jobs is decided by the strategy, other jobs
+    /// report the priority they were queued with.
+    pub fn get_job_priority(&self, uuid: Uuid) -> Option<i32> {
+        if let Some((_, spec)) = self.queued_jobs.iter().find(|job| job.0 == uuid) {
+            return Some(self.strategy.effective_priority(spec));
+        }
+
+        self.job_specs.get(&uuid).map(|spec| spec.priority)
+    }
+
+    /// Get the progress of the job associated with the given UUID, if it is running and the duration of its file is known.
+    pub fn get_job_progress(&self, uuid: Uuid) -> Option<Progress> {
+        let running_job = self.running_jobs.get(&uuid)?;
+        if self.job_statuses.get(&uuid) == Some(&JobStatus::Preprocessing) {
+            return None;
+        }
+        let duration_secs = self.job_metadata.get(&uuid)?.duration_secs?;
+        Some(running_job.progress(running_job.spec.transcribed_duration(duration_secs)))
+    }
+
     /// Start running some of the queued jobs, and report how many new jobs were started
-    pub fn run_queued_jobs(&mut self) -> usize {
+    fn run_queued_jobs(&mut self) -> usize {
+        if self.draining {
+            return 0;
+        }
+
         let mut new_jobs_count = 0;
 
         let mut jobs_to_run = self
             .strategy
-            .select_queued_jobs_to_run(&mut self.queued_commands, &self.running_jobs);
+            .select_queued_jobs_to_run(&mut self.queued_jobs, &self.running_jobs);
 
         loop {
             if jobs_to_run.is_empty() {
@@ -181,16 +679,41 @@ impl Scheduler {
 
             let job = jobs_to_run.pop();
 
-            if let Some(mut job) = job {
-                let child = match job.1.spawn() {
-                    Ok(c) => c,
+            if let Some(job) = job {
+                self.job_specs.insert(job.0, job.1.clone());
+                if let Some(m) = self.job_metadata.get_mut(&job.0) {
+                    m.attempts += 1;
+                    // The strategy may have chosen the model and device
+                    m.model = job.1.model.clone();
+                    m.device = job.1.device.clone();
+                }
+
+                let attempt = self.job_metadata.get(&job.0).map_or(1, |m| m.attempts);
+                let running_job = match RunningJob::spawn(
+                    job.0,
+                    &job.1,
+                    attempt,
+                    self.backend.clone(),
+                    self.events.clone(),
+                ) {
+                    Ok(r) => r,
                     Err(e) => {
-                        log::error!("Failed to run job {:?}: {}", job, e);
+                        log::error!("Failed to run job {}: {}", job.0, e);
+                        self.record_job_failure(
+                            job.0,
+                            FailureKind::StartFailed,
+                            format!("Failed to start process: {}", e),
+                        );
                         continue;
                     }
                 };
-                self.running_jobs.insert(job.0, child);
-                self.job_statuses.insert(job.0, JobStatus::Running);
+                let status = if job.1.preprocessing.is_some() {
+                    JobStatus::Preprocessing
+                } else {
+                    JobStatus::Running
+                };
+                self.running_jobs.insert(job.0, running_job);
+                self.set_job_status(job.0, status);
                 self.update_job_metadata(job.0);
                 new_jobs_count += 1;
             }
@@ -200,21 +723,20 @@ impl Scheduler {
     }
 
     /// Helper function for canceling a job in the job queue.
-    fn cancel_queued_job(&mut self, id: Uuid) -> Result<()> {
-        let idx = self.queued_commands.iter().position(|job| job.0 == id);
+    fn cancel_queued_job(&mut self, id: Uuid) -> Result<(), SchedulerError> {
+        let idx = self.queued_jobs.iter().position(|job| job.0 == id);
         if let Some(idx) = idx {
-            let job = self.queued_commands.remove(idx);
+            let job = self.queued_jobs.remove(idx);
             log::debug!("Removed job {:?}", job);
         } else {
-            let msg = format!("Job with ID {} not found", id);
-            return Err(Error::msg(msg));
+            return Err(SchedulerError::NotQueued(id));
         }
 
         log::debug!("Scheduler after removing job with ID {}: {:?}", id, self);
         Ok(())
     }
 
-    /// Update the timestamp of the metadata associated with the job ID
+    /// Update the timestamp of the metadata associated with the job ID, and persist the job
     fn update_job_metadata(&mut self, id: Uuid) {
         let metadata = self.job_metadata.get_mut(&id);
 
@@ -226,5 +748,64 @@ impl Scheduler {
                 id
             );
         }
+
+        self.persist_job(id);
+
+        // The metadata is updated right after every change of status, so a finished status means the job just finished
+        if self.job_statuses.get(&id).is_some_and(|s| s.is_finished()) {
+            self.report_finished_job(id);
+        }
+    }
+
+    /// Send a job that just finished to the webhook dispatcher.
+    fn report_finished_job(&self, id: Uuid) {
+        let (Some(spec), Some(status), Some(metadata)) = (
+            self.job_specs.get(&id),
+            self.job_statuses.get(&id),
+            self.job_metadata.get(&id),
+        ) else {
+            return;
+        };
+
+        let job = FinishedJob {
+            id,
+            status: status.clone(),
+            metadata: metadata.clone(),
+            callback_url: spec.callback_url.clone(),
+        };
+
+        if let Err(e) = self.finished_jobs.send(job) {
+            log::error!("Could not send finished job {} to webhooks: {}", id, e);
+        }
+    }
+
+    /// Let the event loop know that the scheduler should run again
+    fn notify(&self, event: SchedulerEvent) {
+        if let Err(e) = self.events.send(event) {
+            log::error!("Could not send scheduler event: {}", e);
+        }
+    }
+
+    /// Write the current state of the job to the job store
+    fn persist_job(&mut self, id: Uuid) {
+        let (Some(spec), Some(status), Some(metadata)) = (
+            self.job_specs.get(&id),
+            self.job_statuses.get(&id),
+            self.job_metadata.get(&id),
+        ) else {
+            log::warn!("Attempted to persist job {}, but it is not fully known", id);
+            return;
+        };
+
+        let record = JobRecord {
+            id,
+            spec: spec.clone(),
+            status: status.clone(),
+            metadata: metadata.clone(),
+        };
+
+        if let Err(e) = self.store.append(record) {
+            log::error!("Failed to persist job {}: {}", id, e);
+        }
     }
 }
@@ -0,0 +1,156 @@
+use std::time::Instant;
+
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::ChildStdout,
+    sync::watch,
+};
+
+/// How far a running job got in the file it transcribes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The share of the file that was transcribed, in percent
+    pub percent: f64,
+    /// The estimated time left until the job finishes, in seconds, once something was transcribed
+    pub eta_secs: Option<u64>,
+}
+
+impl Progress {
+    /// Compute the progress of a job that started at the given instant and transcribed the given number of seconds of a file
+    /// lasting `duration_secs`.
+    pub fn compute(started_at: Instant, transcribed_secs: f64, duration_secs: f64) -> Self {
+        if duration_secs <= 0.0 {
+            return Progress {
+                percent: 0.0,
+                eta_secs: None,
+            };
+        }
+
+        let ratio = (transcribed_secs / duration_secs).clamp(0.0, 1.0);
+
+        // Assume the rest of the file is transcribed at the same pace
+        let eta_secs = if ratio > 0.0 {
+            let elapsed_secs = started_at.elapsed().as_secs_f64();
+            Some((elapsed_secs * (1.0 - ratio) / ratio).round() as u64)
+        } else {
+            None
+        };
+
+        Progress {
+            percent: ratio * 100.0,
+            eta_secs,
+        }
+    }
+}
+
+/// Copy the stdout of whisper to the given file, and send the end of the last segment whisper transcribed, in seconds, as it
+/// goes.
+pub async fn read_stdout(
+    stdout: ChildStdout,
+    mut out_file: File,
+    transcribed_secs: watch::Sender<f64>,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(l)) => l,
+            Ok(None) => break,
+            Err(e) => {
+                log::warn!("Could not read the output of whisper: {}", e);
+                break;
+            }
+        };
+
+        if let Some(end) = parse_segment_end(&line) {
+            transcribed_secs.send_replace(end);
+        }
+
+        let written = async {
+            out_file.write_all(line.as_bytes()).await?;
+            out_file.write_all(b"\n").await
+        };
+        if let Err(e) = written.await {
+            log::warn!("Could not write the output of whisper: {}", e);
+        }
+    }
+
+    if let Err(e) = out_file.flush().await {
+        log::warn!("Could not write the output of whisper: {}", e);
+    }
+}
+
+/// Find the end of a segment printed by whisper, e.g. `[01:02.500 --> 01:05.000]  Some text` or
+/// `[01:02:03.500 --> 01:02:05.000]  Some text` for files longer than an hour, in seconds.
+fn parse_segment_end(line: &str) -> Option<f64> {
+    let timestamps = line.trim_start().strip_prefix('[')?.split_once(']')?.0;
+    let (_, end) = timestamps.split_once("-->")?;
+
+    // Seconds, minutes, then hours
+    let mut secs = 0.0;
+    for (idx, part) in end.trim().rsplit(':').enumerate() {
+        let unit = match idx {
+            0 => 1.0,
+            1 => 60.0,
+            2 => 3600.0,
+            _ => return None,
+        };
+        secs += part.parse::<f64>().ok()? * unit;
+    }
+
+    Some(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_segment_ends() {
+        assert_eq!(
+            parse_segment_end("[01:02.500 --> 01:05.000]  Some text"),
+            Some(65.0)
+        );
+        assert_eq!(
+            parse_segment_end("  [01:02:03.500 --> 01:02:05.250]  Some text"),
+            Some(3725.25)
+        );
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        assert_eq!(parse_segment_end("Detected language: English"), None);
+        assert_eq!(parse_segment_end("[not a segment]"), None);
+        assert_eq!(
+            parse_segment_end("[00:01.000 --> 1:2:3:4.0]  Too long"),
+            None
+        );
+    }
+
+    #[test]
+    fn computes_progress() {
+        let progress = Progress::compute(Instant::now(), 30.0, 120.0);
+        assert_eq!(progress.percent, 25.0);
+        assert!(progress.eta_secs.is_some());
+
+        let progress = Progress::compute(Instant::now(), 200.0, 120.0);
+        assert_eq!(progress.percent, 100.0);
+        assert_eq!(progress.eta_secs, Some(0));
+    }
+
+    #[test]
+    fn has_no_eta_before_anything_is_transcribed() {
+        let progress = Progress::compute(Instant::now(), 0.0, 120.0);
+        assert_eq!(
+            progress,
+            Progress {
+                percent: 0.0,
+                eta_secs: None
+            }
+        );
+
+        let progress = Progress::compute(Instant::now(), 10.0, 0.0);
+        assert_eq!(progress.eta_secs, None);
+    }
+}
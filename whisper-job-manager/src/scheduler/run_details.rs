@@ -0,0 +1,117 @@
+use uuid::Uuid;
+
+use crate::workspace::{self, STDERR_FILE, STDOUT_FILE};
+
+/// Details of a run of whisper that are only found in its output.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RunDetails {
+    /// The language whisper detected, as it names it
+    pub detected_language: Option<String>,
+    /// The model whisper loaded
+    pub model: Option<String>,
+    /// The device whisper ran on
+    pub device: Option<String>,
+}
+
+impl RunDetails {
+    /// Read the details from the stdout and stderr files in the workspace of the job with the given UUID.
+    pub async fn read_from_workspace(uuid: Uuid) -> Self {
+        let mut details = RunDetails::default();
+
+        for name in [STDOUT_FILE, STDERR_FILE] {
+            let path = workspace::workspace_file(uuid, name);
+            match tokio::fs::read(path.as_path()).await {
+                Ok(bytes) => details.parse(&String::from_utf8_lossy(&bytes)),
+                Err(e) => log::warn!("Could not read output file {:?}: {}", path, e),
+            }
+        }
+
+        details
+    }
+
+    /// Look for details in the output of whisper. Details that are already known are only replaced by ones found later in the output.
+    pub fn parse(&mut self, output: &str) {
+        for line in output.lines().map(str::trim) {
+            // openai-whisper: "Detected language: French"
+            if let Some(language) = line.strip_prefix("Detected language: ") {
+                self.detected_language = Some(language.trim().to_string());
+            }
+            // whisper.cpp: "whisper_full_with_state: auto-detected language: fr (p = 0.981)"
+            else if let Some((_, rest)) = line.split_once("auto-detected language: ") {
+                if let Some(language) = rest.split_whitespace().next() {
+                    self.detected_language = Some(language.to_string());
+                }
+            }
+            // whisper-ctranslate2: "Detected language 'fr' with probability 0.981"
+            else if let Some(rest) = line.strip_prefix("Detected language '") {
+                if let Some((language, _)) = rest.split_once('\'') {
+                    self.detected_language = Some(language.to_string());
+                }
+            }
+            // openai-whisper falls back to FP32 when running on the CPU
+            else if line.contains("FP16 is not supported on CPU") {
+                self.device = Some(String::from("cpu"));
+            }
+            // whisper.cpp: "whisper_init_from_file_with_params_no_state: loading model from 'models/ggml-base.en.bin'"
+            else if let Some((_, rest)) = line.split_once("loading model from '") {
+                if let Some((path, _)) = rest.split_once('\'') {
+                    let file = path.rsplit('/').next().unwrap_or(path);
+                    let model = file
+                        .strip_prefix("ggml-")
+                        .unwrap_or(file)
+                        .trim_end_matches(".bin");
+                    self.model = Some(model.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_openai_whisper_output() {
+        let mut details = RunDetails::default();
+        details.parse(
+            "UserWarning: FP16 is not supported on CPU; using FP32 instead\nDetected language: French\n[00:00.000 --> 00:01.000]  Bonjour\n",
+        );
+        assert_eq!(
+            details,
+            RunDetails {
+                detected_language: Some(String::from("French")),
+                model: None,
+                device: Some(String::from("cpu")),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_whisper_cpp_output() {
+        let mut details = RunDetails::default();
+        details.parse(
+            "whisper_init_from_file_with_params_no_state: loading model from 'models/ggml-base.en.bin'\nwhisper_full_with_state: auto-detected language: fr (p = 0.981)\n",
+        );
+        assert_eq!(details.model.as_deref(), Some("base.en"));
+        assert_eq!(details.detected_language.as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn parses_faster_whisper_output() {
+        let mut details = RunDetails::default();
+        details.parse("Detected language 'de' with probability 0.97\n");
+        assert_eq!(details.detected_language.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn keeps_the_latest_details() {
+        let mut details = RunDetails::default();
+        details.parse("Detected language: French\n");
+        details.parse("Some other line\n");
+        assert_eq!(details.detected_language.as_deref(), Some("French"));
+
+        details.parse("===== Attempt 2 =====\nDetected language: German\n");
+        assert_eq!(details.detected_language.as_deref(), Some("German"));
+    }
+}
@@ -0,0 +1,390 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    process::{ExitStatus, Stdio},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    process::Child,
+    sync::{mpsc::UnboundedSender, oneshot, watch},
+    task::JoinHandle,
+};
+use uuid::Uuid;
+use whisper_job_manager_models::output_format::OutputFormat;
+
+use crate::{
+    backend::TranscriptionBackend,
+    media, subtitles,
+    workspace::{self, STDERR_FILE, STDOUT_FILE},
+};
+
+use super::{
+    events::SchedulerEvent,
+    job_spec::JobSpec,
+    progress::{self, Progress},
+    run_details::RunDetails,
+};
+
+/// How long to wait for the rest of the output once the process exited
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The shortest time between two reports of the progress of a job to the scheduler
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How the processes of a job ended.
+#[derive(Debug)]
+pub enum JobOutcome {
+    /// The transcription process exited on its own with the given status
+    Exited(ExitStatus),
+    /// The process was killed by the scheduler
+    Killed,
+    /// The process was killed after the job ran for longer than the given maximum runtime
+    TimedOut(Duration),
+    /// The exit of the process could not be awaited
+    WaitFailed(String),
+    /// The transcription process could not be started
+    StartFailed(String),
+    /// Preparing the source with ffmpeg failed
+    PreprocessingFailed(String),
+    /// The transcription process succeeded, but the timestamps of its outputs could not be shifted back to match the source
+    ShiftFailed(String),
+}
+
+/// A job whose processes are running. The processes are watched by a task that reports the end of the job to the scheduler as a
+/// `SchedulerEvent::JobExited` event, after reporting the end of preprocessing as a `SchedulerEvent::PreprocessingFinished` event if the
+/// job is preprocessed.
+#[derive(Debug)]
+pub struct RunningJob {
+    /// The spec the job was started with
+    pub spec: JobSpec,
+    /// Signal for the watching task to kill the process. Dropping it kills the process as well.
+    kill: Option<oneshot::Sender<()>>,
+    /// The task watching the processes
+    handle: JoinHandle<()>,
+    /// When the transcription process started, or the job if it did not start yet
+    started_at: watch::Receiver<Instant>,
+    /// The end of the last segment whisper transcribed, in seconds
+    transcribed_secs: watch::Receiver<f64>,
+}
+
+impl RunningJob {
+    /// Start the given attempt of the job, preparing its source first if it asks for it, then transcribing it with the given backend. A
+    /// task watches the processes until they exit.
+    pub fn spawn(
+        id: Uuid,
+        spec: &JobSpec,
+        attempt: u32,
+        backend: Arc<dyn TranscriptionBackend>,
+        events: UnboundedSender<SchedulerEvent>,
+    ) -> std::io::Result<Self> {
+        let out_file = open_log_file(id, STDOUT_FILE, attempt)?;
+        let err_file = open_log_file(id, STDERR_FILE, attempt)?;
+
+        // The preprocessing process is started right away, so a missing ffmpeg fails the job like a missing whisper does
+        let preprocessing = match &spec.preprocessing {
+            Some(params) => {
+                let output = workspace::preprocessed_path(id, spec.source.as_path());
+                let child = media::preprocess_command(
+                    spec.source.as_path(),
+                    output.as_path(),
+                    params,
+                    spec.start_secs,
+                    spec.end_secs,
+                )
+                .stdout(Stdio::null())
+                .stderr(err_file.try_clone()?)
+                .kill_on_drop(true)
+                .spawn()?;
+                Some((child, output))
+            }
+            None => None,
+        };
+
+        let (started_tx, started_rx) = watch::channel(Instant::now());
+        let (transcribed_tx, transcribed_rx) = watch::channel(0.0);
+        let (kill_tx, mut kill_rx) = oneshot::channel::<()>();
+        let max_runtime = spec.max_runtime();
+        let deadline = max_runtime.map(|d| tokio::time::Instant::now() + d);
+        let spec = spec.clone();
+        let task_spec = spec.clone();
+
+        tokio::spawn(report_progress(id, transcribed_rx.clone(), events.clone()));
+
+        let handle = tokio::spawn(async move {
+            let spec = task_spec;
+            let watcher = Watcher {
+                id,
+                max_runtime,
+                deadline,
+            };
+
+            let mut transcription_spec = spec.clone();
+            let mut preprocessed_file = None;
+
+            if let Some((mut child, output)) = preprocessing {
+                let outcome = watcher.wait(&mut child, &mut kill_rx).await;
+                log::debug!("Preprocessing of job {} ended with {:?}", id, outcome);
+
+                let outcome = match outcome {
+                    JobOutcome::Exited(s) if s.success() => None,
+                    JobOutcome::Exited(s) => Some(JobOutcome::PreprocessingFailed(format!(
+                        "ffmpeg exited with {}",
+                        s
+                    ))),
+                    o => Some(o),
+                };
+
+                if let Some(outcome) = outcome {
+                    remove_preprocessed_file(output.as_path()).await;
+                    report_exit(&events, id, outcome).await;
+                    return;
+                }
+
+                if let Err(e) = events.send(SchedulerEvent::PreprocessingFinished(id)) {
+                    log::error!("Could not report the preprocessing of job {}: {}", id, e);
+                }
+
+                transcription_spec.source = output.clone();
+                preprocessed_file = Some(output);
+            }
+
+            let outcome = match watcher
+                .transcribe(
+                    &transcription_spec,
+                    backend.as_ref(),
+                    out_file,
+                    err_file,
+                    ProgressSenders {
+                        started_tx,
+                        transcribed_tx,
+                    },
+                    &mut kill_rx,
+                )
+                .await
+            {
+                Ok(JobOutcome::Exited(s)) if s.success() => {
+                    match shift_outputs(id, &spec, backend.as_ref()).await {
+                        Ok(()) => JobOutcome::Exited(s),
+                        Err(e) => JobOutcome::ShiftFailed(format!("{:#}", e)),
+                    }
+                }
+                Ok(outcome) => outcome,
+                Err(e) => JobOutcome::StartFailed(e.to_string()),
+            };
+
+            if let Some(file) = preprocessed_file {
+                remove_preprocessed_file(file.as_path()).await;
+            }
+
+            report_exit(&events, id, outcome).await;
+        });
+
+        Ok(RunningJob {
+            spec,
+            kill: Some(kill_tx),
+            handle,
+            started_at: started_rx,
+            transcribed_secs: transcribed_rx,
+        })
+    }
+
+    /// Kill the process, and wait until it has exited.
+    pub async fn kill(mut self) {
+        if let Some(kill) = self.kill.take() {
+            // An error means the task already ended, which is fine
+            let _ = kill.send(());
+        }
+
+        if let Err(e) = (&mut self.handle).await {
+            log::error!("Task watching a killed job ended abnormally: {}", e);
+        }
+    }
+
+    /// How far the job got in a file lasting the given number of seconds.
+    pub fn progress(&self, duration_secs: f64) -> Progress {
+        Progress::compute(
+            *self.started_at.borrow(),
+            *self.transcribed_secs.borrow(),
+            duration_secs,
+        )
+    }
+
+    /// Check whether the task watching the process has ended.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+/// Watches the processes of a job, one after the other.
+struct Watcher {
+    id: Uuid,
+    max_runtime: Option<Duration>,
+    /// When the job runs out of time, which is shared by all of its processes
+    deadline: Option<tokio::time::Instant>,
+}
+
+/// Where the transcription process reports how far it got.
+struct ProgressSenders {
+    /// When the transcription process started
+    started_tx: watch::Sender<Instant>,
+    /// The end of the last segment whisper transcribed
+    transcribed_tx: watch::Sender<f64>,
+}
+
+impl Watcher {
+    /// Wait for a process to exit, killing it if the scheduler asks for it or the job runs out of time.
+    async fn wait(&self, child: &mut Child, kill_rx: &mut oneshot::Receiver<()>) -> JobOutcome {
+        let id = self.id;
+        let timeout = async {
+            match self.deadline {
+                Some(d) => tokio::time::sleep_until(d).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            exit_status = child.wait() => match exit_status {
+                Ok(s) => JobOutcome::Exited(s),
+                Err(e) => {
+                    log::warn!("Could not find the exit status of job {}, attempting to kill process: {}", id, e);
+                    if let Err(kill_e) = child.kill().await {
+                        log::error!("Could not kill job {}: {}", id, kill_e);
+                    }
+                    JobOutcome::WaitFailed(e.to_string())
+                }
+            },
+            _ = kill_rx => {
+                if let Err(e) = child.kill().await {
+                    log::error!("Failed to kill child process {:?}: {}", child, e);
+                }
+                JobOutcome::Killed
+            }
+            _ = timeout => {
+                // Unwrap ok because the timeout never completes without a maximum runtime
+                let max_runtime = self.max_runtime.unwrap();
+                log::warn!("Job {} ran for longer than {:?}, killing process", id, max_runtime);
+                if let Err(e) = child.kill().await {
+                    log::error!("Failed to kill child process {:?}: {}", child, e);
+                }
+                JobOutcome::TimedOut(max_runtime)
+            }
+        }
+    }
+
+    /// Run the transcription process of the backend, and wait for it to exit and for its output to be read.
+    async fn transcribe(
+        &self,
+        spec: &JobSpec,
+        backend: &dyn TranscriptionBackend,
+        out_file: File,
+        err_file: File,
+        progress: ProgressSenders,
+        kill_rx: &mut oneshot::Receiver<()>,
+    ) -> std::io::Result<JobOutcome> {
+        let id = self.id;
+        let mut child = backend
+            .build_command(id, spec)?
+            .stdout(Stdio::piped())
+            .stderr(err_file)
+            .kill_on_drop(true)
+            .spawn()?;
+        progress.started_tx.send_replace(Instant::now());
+
+        let reader = child.stdout.take().map(|stdout| {
+            tokio::spawn(progress::read_stdout(
+                stdout,
+                tokio::fs::File::from_std(out_file),
+                progress.transcribed_tx,
+            ))
+        });
+
+        let outcome = self.wait(&mut child, kill_rx).await;
+        log::debug!("Job {} ended with outcome {:?}", id, outcome);
+
+        if let Some(reader) = reader {
+            if tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, reader)
+                .await
+                .is_err()
+            {
+                log::warn!("Output of job {} did not end with its process", id);
+            }
+        }
+
+        Ok(outcome)
+    }
+}
+
+/// Open a log file of a job, keeping what earlier attempts logged, and mark where the given attempt starts.
+fn open_log_file(id: Uuid, name: &str, attempt: u32) -> std::io::Result<File> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(workspace::workspace_file(id, name))?;
+
+    if attempt > 1 {
+        writeln!(file, "===== Attempt {} =====", attempt)?;
+    }
+
+    Ok(file)
+}
+
+/// Shift the timestamps of the outputs of a job that only transcribed part of its source, so they match the whole source.
+async fn shift_outputs(
+    id: Uuid,
+    spec: &JobSpec,
+    backend: &dyn TranscriptionBackend,
+) -> anyhow::Result<()> {
+    let Some(offset_secs) = spec.output_offset_secs() else {
+        return Ok(());
+    };
+
+    for format in spec.output_formats.iter() {
+        if *format == OutputFormat::Txt {
+            continue;
+        }
+        if let Some(path) = backend.find_output(id, *format) {
+            subtitles::shift_file(path.as_path(), *format, offset_secs).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the WAV file a source was prepared to, which is not needed once it is transcribed.
+async fn remove_preprocessed_file(path: &std::path::Path) {
+    if let Err(e) = tokio::fs::remove_file(path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("Could not remove preprocessed file {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Report to the scheduler whenever whisper transcribed more of the file, at most once per interval, until the job ends.
+async fn report_progress(
+    id: Uuid,
+    mut transcribed_rx: watch::Receiver<f64>,
+    events: UnboundedSender<SchedulerEvent>,
+) {
+    // Fails once the job ended, and dropped the sender
+    while transcribed_rx.changed().await.is_ok() {
+        if events.send(SchedulerEvent::ProgressReported(id)).is_err() {
+            break;
+        }
+        tokio::time::sleep(PROGRESS_REPORT_INTERVAL).await;
+    }
+}
+
+/// Read the details of the run from its output, and report the end of the job to the scheduler.
+async fn report_exit(events: &UnboundedSender<SchedulerEvent>, id: Uuid, outcome: JobOutcome) {
+    let details = RunDetails::read_from_workspace(id).await;
+
+    if let Err(e) = events.send(SchedulerEvent::JobExited {
+        id,
+        outcome,
+        details,
+    }) {
+        log::error!("Could not report the exit of job {}: {}", id, e);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::scheduler::{job_spec::JobSpec, runner::RunningJob};
+
+use super::{
+    simple::{self, SimpleSchedulerStrategy, SimpleStrategyParams},
+    SchedulerStrategy,
+};
+
+/// Parameters of the fair share strategy in the config.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct FairShareStrategyParams {
+    /// Weight of each submitter, a submitter with twice the weight of another gets twice the running jobs
+    pub weights: HashMap<String, f64>,
+    /// Weight of submitters not listed in `weights`
+    pub default_weight: f64,
+    /// The maximum number of jobs a single submitter can have running at once, if any
+    pub max_jobs_per_submitter: Option<usize>,
+    /// How many jobs run at once and on what devices, see the simple strategy
+    pub slots: SimpleStrategyParams,
+}
+
+impl Default for FairShareStrategyParams {
+    fn default() -> Self {
+        Self {
+            weights: HashMap::new(),
+            default_weight: 1.0,
+            max_jobs_per_submitter: None,
+            slots: SimpleStrategyParams::default(),
+        }
+    }
+}
+
+/// Scheduler strategy that shares the running jobs across submitters. Every free slot goes to the submitter with the fewest running jobs relative
+/// to its weight, and then to the submitter that was served the least relative to its weight, taking the job of that submitter that was queued
+/// first. This way one submitter with a long queue cannot hold every slot while others wait. Jobs are then assigned to devices like the simple
+/// strategy does.
+#[derive(Debug)]
+pub struct FairShareSchedulerStrategy {
+    weights: HashMap<String, f64>,
+    default_weight: f64,
+    max_jobs_per_submitter: Option<usize>,
+    slots: SimpleSchedulerStrategy,
+    /// How much each active submitter was served, going up by the inverse of its weight for every job started
+    served: HashMap<String, f64>,
+}
+
+impl FairShareSchedulerStrategy {
+    /// Create the strategy from its parameters in the config.
+    pub fn from_params(params: serde_json::Value) -> Result<Box<dyn SchedulerStrategy>> {
+        let params: FairShareStrategyParams = super::parse_params(params)?;
+
+        if params.default_weight <= 0.0 {
+            return Err(Error::msg("defaultWeight must be greater than 0"));
+        }
+
+        if let Some((submitter, _)) = params.weights.iter().find(|(_, w)| **w <= 0.0) {
+            return Err(Error::msg(format!(
+                "The weight of submitter \"{}\" must be greater than 0",
+                submitter
+            )));
+        }
+
+        if params.max_jobs_per_submitter == Some(0) {
+            return Err(Error::msg("maxJobsPerSubmitter must be greater than 0"));
+        }
+
+        Ok(Box::new(FairShareSchedulerStrategy {
+            weights: params.weights,
+            default_weight: params.default_weight,
+            max_jobs_per_submitter: params.max_jobs_per_submitter,
+            slots: SimpleSchedulerStrategy::new(params.slots)?,
+            served: HashMap::new(),
+        }))
+    }
+
+    fn weight(&self, submitter: &str) -> f64 {
+        self.weights
+            .get(submitter)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+}
+
+impl SchedulerStrategy for FairShareSchedulerStrategy {
+    fn select_queued_jobs_to_run(
+        &mut self,
+        queued_jobs: &mut VecDeque<(Uuid, JobSpec)>,
+        running_jobs: &HashMap<Uuid, RunningJob>,
+    ) -> Vec<(Uuid, JobSpec)> {
+        let free_slots = self.slots.max_jobs().saturating_sub(running_jobs.len());
+        let mut jobs = Vec::with_capacity(free_slots);
+        let mut busy_devices = simple::busy_devices(running_jobs);
+
+        // Running jobs of each submitter
+        let mut running_counts: HashMap<String, usize> = HashMap::new();
+        for job in running_jobs.values() {
+            *running_counts
+                .entry(job.spec.submitter.clone())
+                .or_default() += 1;
+        }
+
+        // Forget submitters that have nothing queued or running, and let new submitters start level with the least served active one, so
+        // being idle for a while does not earn a submitter a burst of slots later
+        self.served.retain(|submitter, _| {
+            running_counts.contains_key(submitter)
+                || queued_jobs.iter().any(|job| &job.1.submitter == submitter)
+        });
+        let least_served = self
+            .served
+            .values()
+            .copied()
+            .reduce(f64::min)
+            .unwrap_or(0.0);
+        for (_, spec) in queued_jobs.iter() {
+            self.served
+                .entry(spec.submitter.clone())
+                .or_insert(least_served);
+        }
+
+        for _ in 0..free_slots {
+            // The first queued job of every submitter that can run more jobs
+            let mut candidates: HashMap<&str, usize> = HashMap::new();
+            for (idx, (_, spec)) in queued_jobs.iter().enumerate() {
+                let running = running_counts.get(&spec.submitter).copied().unwrap_or(0);
+                if self
+                    .max_jobs_per_submitter
+                    .is_some_and(|max| running >= max)
+                {
+                    continue;
+                }
+                candidates.entry(spec.submitter.as_str()).or_insert(idx);
+            }
+
+            // The submitter with the smallest share of running jobs, then the least served one, then the one that has waited the longest
+            let selected = candidates
+                .iter()
+                .map(|(submitter, idx)| {
+                    let running = running_counts.get(*submitter).copied().unwrap_or(0);
+                    let served = self.served.get(*submitter).copied().unwrap_or(0.0);
+                    (running as f64 / self.weight(submitter), served, *idx)
+                })
+                .min_by(|a, b| {
+                    a.0.total_cmp(&b.0)
+                        .then(a.1.total_cmp(&b.1))
+                        .then(a.2.cmp(&b.2))
+                })
+                .map(|(_, _, idx)| idx);
+
+            let Some(mut job) = selected.and_then(|idx| queued_jobs.remove(idx)) else {
+                break;
+            };
+
+            *running_counts.entry(job.1.submitter.clone()).or_default() += 1;
+            let weight = self.weight(&job.1.submitter);
+            *self.served.entry(job.1.submitter.clone()).or_default() += 1.0 / weight;
+            self.slots.update_job(&mut job, &mut busy_devices);
+            jobs.push(job);
+        }
+
+        jobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::strategy::tests::{queue, sources};
+
+    use super::*;
+
+    fn strategy(params: serde_json::Value) -> Box<dyn SchedulerStrategy> {
+        FairShareSchedulerStrategy::from_params(params).unwrap()
+    }
+
+    #[test]
+    fn shares_slots_across_submitters() {
+        let mut queued = queue(4, |idx, spec| {
+            spec.submitter = String::from(["a", "a", "a", "b"][idx]);
+        });
+        let jobs = strategy(serde_json::json!({ "slots": { "maxJobs": 2 } }))
+            .select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(sources(&jobs), ["0.mp3", "3.mp3"]);
+    }
+
+    #[test]
+    fn gives_more_slots_to_heavier_submitters() {
+        let mut strategy = strategy(serde_json::json!({
+            "weights": { "a": 2.0 },
+            "slots": { "maxJobs": 1 }
+        }));
+        let mut queued = queue(6, |idx, spec| {
+            spec.submitter = String::from(["a", "a", "a", "b", "b", "b"][idx]);
+        });
+
+        // One slot at a time, as if every job finished right away
+        let mut order = vec![];
+        for _ in 0..6 {
+            let jobs = strategy.select_queued_jobs_to_run(&mut queued, &HashMap::new());
+            order.extend(jobs.into_iter().map(|(_, spec)| spec.submitter));
+        }
+
+        assert_eq!(order, ["a", "b", "a", "a", "b", "b"]);
+    }
+
+    #[test]
+    fn limits_the_jobs_of_each_submitter() {
+        let mut queued = queue(3, |_, spec| spec.submitter = String::from("a"));
+        let jobs = strategy(serde_json::json!({ "maxJobsPerSubmitter": 1 }))
+            .select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(sources(&jobs), ["0.mp3"]);
+    }
+
+    #[test]
+    fn rejects_invalid_weights() {
+        for params in [
+            serde_json::json!({ "defaultWeight": 0.0 }),
+            serde_json::json!({ "weights": { "a": -1.0 } }),
+            serde_json::json!({ "maxJobsPerSubmitter": 0 }),
+        ] {
+            assert!(FairShareSchedulerStrategy::from_params(params).is_err());
+        }
+    }
+}
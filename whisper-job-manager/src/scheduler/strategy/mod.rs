@@ -0,0 +1,130 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Context, Error, Result};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::config::SchedulerConfig;
+
+use super::{job_spec::JobSpec, runner::RunningJob};
+
+pub mod fair_share;
+pub mod priority;
+pub mod resource_budget;
+pub mod simple;
+
+/// Strategy to determine what jobs to run, as well as to make any changes to the commands ran when the job is run.
+pub trait SchedulerStrategy: std::fmt::Debug + Send + Sync {
+    /// Select queried jobs to run. The jobs selected to run will be removed from `queued_jobs` and provided in the returned `Vec`. This method can modify job specs
+    /// to run with different properties depending on the implementation of the strategy.
+    fn select_queued_jobs_to_run(
+        &mut self,
+        queued_jobs: &mut VecDeque<(Uuid, JobSpec)>,
+        running_jobs: &HashMap<Uuid, RunningJob>,
+    ) -> Vec<(Uuid, JobSpec)>;
+
+    /// The effective priority of a queued job. Strategies that do not use priorities report the priority the job was queued with.
+    fn effective_priority(&self, spec: &JobSpec) -> i32 {
+        spec.priority
+    }
+}
+
+/// Function creating a strategy from its parameters in the config.
+type StrategyFactory = fn(serde_json::Value) -> Result<Box<dyn SchedulerStrategy>>;
+
+/// All strategies that can be selected in the config, by name.
+const STRATEGIES: &[(&str, StrategyFactory)] = &[
+    ("simple", simple::SimpleSchedulerStrategy::from_params),
+    ("priority", priority::PrioritySchedulerStrategy::from_params),
+    (
+        "fairShare",
+        fair_share::FairShareSchedulerStrategy::from_params,
+    ),
+    (
+        "resourceBudget",
+        resource_budget::ResourceBudgetSchedulerStrategy::from_params,
+    ),
+];
+
+/// Create the strategy selected in the config, with the parameters given in the config.
+pub fn build_strategy(config: &SchedulerConfig) -> Result<Box<dyn SchedulerStrategy>> {
+    let factory = STRATEGIES
+        .iter()
+        .find(|(name, _)| *name == config.strategy)
+        .map(|(_, factory)| factory);
+
+    let Some(factory) = factory else {
+        let names: Vec<&str> = STRATEGIES.iter().map(|(name, _)| *name).collect();
+        return Err(Error::msg(format!(
+            "Unknown scheduler strategy \"{}\", expected one of: {}",
+            config.strategy,
+            names.join(", ")
+        )));
+    };
+
+    factory(config.params.clone()).with_context(|| {
+        format!(
+            "Invalid parameters for scheduler strategy \"{}\"",
+            config.strategy
+        )
+    })
+}
+
+/// Parse the parameters of a strategy, treating missing parameters as an empty object.
+fn parse_params<T: DeserializeOwned>(params: serde_json::Value) -> Result<T> {
+    let params = match params {
+        serde_json::Value::Null => serde_json::Value::Object(serde_json::Map::new()),
+        p => p,
+    };
+
+    Ok(serde_json::from_value(params)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A queue of jobs with the given specs, queued one millisecond apart in order. The specs are changed by `update` first.
+    pub fn queue(count: usize, update: impl Fn(usize, &mut JobSpec)) -> VecDeque<(Uuid, JobSpec)> {
+        let queued_at = chrono::offset::Utc::now();
+
+        (0..count)
+            .map(|idx| {
+                let mut spec = JobSpec::new(PathBuf::from(format!("{}.mp3", idx)));
+                spec.queued_at = queued_at + chrono::Duration::milliseconds(idx as i64);
+                update(idx, &mut spec);
+                (Uuid::new_v4(), spec)
+            })
+            .collect()
+    }
+
+    /// The sources of the selected jobs, to compare them easily.
+    pub fn sources(jobs: &[(Uuid, JobSpec)]) -> Vec<String> {
+        jobs.iter()
+            .map(|(_, spec)| spec.source.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn config(strategy: &str, params: serde_json::Value) -> SchedulerConfig {
+        SchedulerConfig {
+            strategy: String::from(strategy),
+            params,
+            ..SchedulerConfig::default()
+        }
+    }
+
+    #[test]
+    fn builds_every_strategy_with_default_params() {
+        for (name, _) in STRATEGIES {
+            assert!(build_strategy(&config(name, serde_json::Value::Null)).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_strategies_and_params() {
+        assert!(build_strategy(&config("unknown", serde_json::Value::Null)).is_err());
+        assert!(build_strategy(&config("simple", serde_json::json!({ "unknown": 1 }))).is_err());
+    }
+}
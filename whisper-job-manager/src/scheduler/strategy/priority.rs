@@ -0,0 +1,176 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::scheduler::{job_spec::JobSpec, runner::RunningJob};
+
+use super::{
+    simple::{SimpleSchedulerStrategy, SimpleStrategyParams},
+    SchedulerStrategy,
+};
+
+/// Parameters of the priority strategy in the config.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct PriorityStrategyParams {
+    /// Every time a job waited this many seconds in the queue, its priority goes up by `agingStep`
+    pub aging_interval_secs: u64,
+    /// How much the priority of a job goes up every aging interval. `0` disables aging, and it cannot be negative.
+    pub aging_step: i32,
+    /// How many jobs run at once and on what devices, see the simple strategy
+    pub slots: SimpleStrategyParams,
+}
+
+impl Default for PriorityStrategyParams {
+    fn default() -> Self {
+        Self {
+            aging_interval_secs: 60 * 10,
+            aging_step: 1,
+            slots: SimpleStrategyParams::default(),
+        }
+    }
+}
+
+/// Scheduler strategy that runs the queued jobs with the highest priority first, and jobs with the same priority in the order they were queued.
+/// The priority of a job goes up the longer it waits, so jobs with a low priority are not starved forever. Jobs are then assigned to devices like
+/// the simple strategy does.
+#[derive(Debug)]
+pub struct PrioritySchedulerStrategy {
+    aging_interval_secs: u64,
+    aging_step: i32,
+    slots: SimpleSchedulerStrategy,
+}
+
+impl PrioritySchedulerStrategy {
+    /// Create the strategy from its parameters in the config.
+    pub fn from_params(params: serde_json::Value) -> Result<Box<dyn SchedulerStrategy>> {
+        let params: PriorityStrategyParams = super::parse_params(params)?;
+
+        if params.aging_interval_secs == 0 {
+            return Err(Error::msg("agingIntervalSecs must be greater than 0"));
+        }
+
+        if params.aging_step < 0 {
+            return Err(Error::msg("agingStep cannot be negative"));
+        }
+
+        Ok(Box::new(PrioritySchedulerStrategy {
+            aging_interval_secs: params.aging_interval_secs,
+            aging_step: params.aging_step,
+            slots: SimpleSchedulerStrategy::new(params.slots)?,
+        }))
+    }
+
+    /// The priority of a queued job at the given time, once it aged.
+    fn priority_at(&self, spec: &JobSpec, now: DateTime<Utc>) -> i32 {
+        let waited_secs = (now - spec.queued_at).num_seconds().max(0) as u64;
+        let intervals = i32::try_from(waited_secs / self.aging_interval_secs).unwrap_or(i32::MAX);
+
+        spec.priority
+            .saturating_add(intervals.saturating_mul(self.aging_step))
+    }
+}
+
+impl SchedulerStrategy for PrioritySchedulerStrategy {
+    fn select_queued_jobs_to_run(
+        &mut self,
+        queued_jobs: &mut VecDeque<(Uuid, JobSpec)>,
+        running_jobs: &HashMap<Uuid, RunningJob>,
+    ) -> Vec<(Uuid, JobSpec)> {
+        // Every job is aged to the same time, so the order does not change while sorting. Jobs with the same priority run in the order
+        // they were queued, whatever order earlier sorts left them in.
+        let now = chrono::offset::Utc::now();
+        queued_jobs.make_contiguous().sort_by_key(|(id, spec)| {
+            (
+                std::cmp::Reverse(self.priority_at(spec, now)),
+                spec.queued_at,
+                *id,
+            )
+        });
+
+        self.slots
+            .select_queued_jobs_to_run(queued_jobs, running_jobs)
+    }
+
+    fn effective_priority(&self, spec: &JobSpec) -> i32 {
+        self.priority_at(spec, chrono::offset::Utc::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::strategy::tests::{queue, sources};
+
+    use super::*;
+
+    fn strategy(aging_step: i32) -> PrioritySchedulerStrategy {
+        PrioritySchedulerStrategy {
+            aging_interval_secs: 60,
+            aging_step,
+            slots: SimpleSchedulerStrategy::new(SimpleStrategyParams {
+                max_jobs: 2,
+                ..SimpleStrategyParams::default()
+            })
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn runs_higher_priorities_first_then_in_queue_order() {
+        let mut queued = queue(4, |idx, spec| spec.priority = [0, 5, 0, 5][idx]);
+        let jobs = strategy(0).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(sources(&jobs), ["1.mp3", "3.mp3"]);
+        assert_eq!(sources(&Vec::from(queued)), ["0.mp3", "2.mp3"]);
+    }
+
+    #[test]
+    fn ages_jobs_waiting_in_the_queue() {
+        let now = chrono::offset::Utc::now();
+        let mut queued = queue(3, |idx, spec| {
+            spec.priority = [0, 2, 2][idx];
+            spec.queued_at = now - chrono::Duration::seconds([60 * 3, 2, 1][idx]);
+        });
+
+        let mut strategy = strategy(1);
+        assert_eq!(strategy.priority_at(&queued[0].1, now), 3);
+
+        let jobs = strategy.select_queued_jobs_to_run(&mut queued, &HashMap::new());
+        assert_eq!(sources(&jobs), ["0.mp3", "1.mp3"]);
+    }
+
+    #[test]
+    fn runs_jobs_tied_by_aging_in_queue_order() {
+        let now = chrono::offset::Utc::now();
+        // An earlier sort left the newer job with a higher priority first
+        let mut queued = queue(3, |idx, spec| {
+            spec.priority = [3, 0, 0][idx];
+            spec.queued_at = now - chrono::Duration::seconds([0, 60 * 3, 0][idx]);
+        });
+
+        let jobs = strategy(1).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+        assert_eq!(sources(&jobs), ["1.mp3", "0.mp3"]);
+    }
+
+    #[test]
+    fn does_not_overflow_priorities() {
+        let now = chrono::offset::Utc::now();
+        let mut spec = JobSpec::new(std::path::PathBuf::from("a.mp3"));
+        spec.priority = i32::MAX - 1;
+        spec.queued_at = now - chrono::Duration::days(365);
+
+        assert_eq!(strategy(i32::MAX).priority_at(&spec, now), i32::MAX);
+    }
+
+    #[test]
+    fn rejects_invalid_aging() {
+        let params = |interval: u64, step: i32| serde_json::json!({ "agingIntervalSecs": interval, "agingStep": step });
+
+        assert!(PrioritySchedulerStrategy::from_params(params(60, 0)).is_ok());
+        assert!(PrioritySchedulerStrategy::from_params(params(0, 1)).is_err());
+        assert!(PrioritySchedulerStrategy::from_params(params(60, -1)).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::scheduler::{job_spec::JobSpec, runner::RunningJob};
+
+use super::SchedulerStrategy;
+
+/// Parameters of the resource budget strategy in the config.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct ResourceBudgetStrategyParams {
+    /// The number of CPU cores jobs can use in total
+    pub cpu_cores: u32,
+    /// The memory jobs can use in total, in megabytes
+    pub memory_mb: u64,
+    /// The number of cores each job reserves, passed to whisper as the number of threads
+    pub threads_per_job: u32,
+    /// Estimated memory used by a job running each model, in megabytes. Models not listed use the largest estimate.
+    pub model_memory_mb: HashMap<String, u64>,
+    /// The model jobs run with, unless the job asks for one
+    pub default_model: String,
+    /// The device jobs run on
+    pub device: String,
+    /// Whether jobs that fit in the remaining budget can start before an older job that does not fit. This uses the budget better, but jobs
+    /// needing a large share of the budget can wait for a long time.
+    pub backfill: bool,
+}
+
+impl Default for ResourceBudgetStrategyParams {
+    fn default() -> Self {
+        let cpu_cores = std::thread::available_parallelism()
+            .map(|n| n.get() as u32)
+            .unwrap_or(1);
+
+        // Estimates of the required memory from the whisper documentation
+        let model_memory_mb = [
+            ("tiny", 1000),
+            ("base", 1000),
+            ("small", 2000),
+            ("medium", 5000),
+            ("turbo", 6000),
+            ("large", 10000),
+        ]
+        .into_iter()
+        .map(|(model, memory)| (String::from(model), memory))
+        .collect();
+
+        Self {
+            cpu_cores,
+            memory_mb: 16000,
+            threads_per_job: cpu_cores.min(4),
+            model_memory_mb,
+            default_model: String::from("large"),
+            device: String::from("cpu"),
+            backfill: false,
+        }
+    }
+}
+
+/// Scheduler strategy for machines without a GPU that only runs jobs fitting in a budget of CPU cores and memory. Each job reserves a number of
+/// cores, which whisper uses as its number of threads, and an estimate of the memory used by its model. Jobs run in the order they were queued.
+#[derive(Debug)]
+pub struct ResourceBudgetSchedulerStrategy {
+    params: ResourceBudgetStrategyParams,
+    /// Memory estimate of models not listed in the parameters
+    unknown_model_memory_mb: u64,
+}
+
+impl ResourceBudgetSchedulerStrategy {
+    /// Create the strategy from its parameters in the config.
+    pub fn from_params(params: serde_json::Value) -> Result<Box<dyn SchedulerStrategy>> {
+        let params: ResourceBudgetStrategyParams = super::parse_params(params)?;
+
+        if params.cpu_cores == 0 {
+            return Err(Error::msg("cpuCores must be greater than 0"));
+        }
+
+        if params.memory_mb == 0 {
+            return Err(Error::msg("memoryMb must be greater than 0"));
+        }
+
+        if params.threads_per_job == 0 || params.threads_per_job > params.cpu_cores {
+            return Err(Error::msg(format!(
+                "threadsPerJob must be between 1 and cpuCores ({})",
+                params.cpu_cores
+            )));
+        }
+
+        let Some(unknown_model_memory_mb) = params.model_memory_mb.values().copied().max() else {
+            return Err(Error::msg("modelMemoryMb must list at least one model"));
+        };
+
+        Ok(Box::new(ResourceBudgetSchedulerStrategy {
+            params,
+            unknown_model_memory_mb,
+        }))
+    }
+
+    /// The estimated memory used by a job running the given model.
+    fn memory_mb(&self, model: Option<&str>) -> u64 {
+        let model = model.unwrap_or(&self.params.default_model);
+        self.params
+            .model_memory_mb
+            .get(model)
+            .copied()
+            .unwrap_or(self.unknown_model_memory_mb)
+    }
+}
+
+impl SchedulerStrategy for ResourceBudgetSchedulerStrategy {
+    fn select_queued_jobs_to_run(
+        &mut self,
+        queued_jobs: &mut VecDeque<(Uuid, JobSpec)>,
+        running_jobs: &HashMap<Uuid, RunningJob>,
+    ) -> Vec<(Uuid, JobSpec)> {
+        let mut jobs = vec![];
+
+        // Resources reserved by running jobs
+        let mut used_cores: u32 = running_jobs
+            .values()
+            .map(|job| job.spec.threads.unwrap_or(self.params.threads_per_job))
+            .sum();
+        let mut used_memory_mb: u64 = running_jobs
+            .values()
+            .map(|job| self.memory_mb(job.spec.model.as_deref()))
+            .sum();
+
+        let mut idx = 0;
+        while idx < queued_jobs.len() {
+            let spec = &queued_jobs[idx].1;
+            let cores = self.params.threads_per_job;
+            let memory_mb = self.memory_mb(spec.model.as_deref());
+
+            let fits = used_cores + cores <= self.params.cpu_cores
+                && used_memory_mb + memory_mb <= self.params.memory_mb;
+
+            // A job that needs more than the whole budget would never run, so it runs when nothing else does
+            let nothing_running = running_jobs.is_empty() && jobs.is_empty();
+            if !fits && nothing_running {
+                log::warn!(
+                    "Job {} needs more than the whole budget, running it alone",
+                    queued_jobs[idx].0
+                );
+            }
+
+            if fits || nothing_running {
+                // Unwrap ok because the index is in bounds
+                let mut job = queued_jobs.remove(idx).unwrap();
+                if job.1.model.is_none() {
+                    job.1.model = Some(self.params.default_model.clone());
+                }
+                job.1.device = Some(self.params.device.clone());
+                job.1.threads = Some(cores);
+
+                used_cores += cores;
+                used_memory_mb += memory_mb;
+                jobs.push(job);
+            } else if self.params.backfill {
+                idx += 1;
+            } else {
+                break;
+            }
+        }
+
+        jobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::strategy::tests::{queue, sources};
+
+    use super::*;
+
+    fn strategy(memory_mb: u64, backfill: bool) -> Box<dyn SchedulerStrategy> {
+        ResourceBudgetSchedulerStrategy::from_params(serde_json::json!({
+            "cpuCores": 8,
+            "memoryMb": memory_mb,
+            "threadsPerJob": 2,
+            "modelMemoryMb": { "tiny": 1000, "medium": 5000, "large": 10000 },
+            "backfill": backfill,
+        }))
+        .unwrap()
+    }
+
+    fn models(models: &'static [&'static str]) -> VecDeque<(Uuid, JobSpec)> {
+        queue(models.len(), |idx, spec| {
+            spec.model = Some(String::from(models[idx]))
+        })
+    }
+
+    #[test]
+    fn runs_jobs_fitting_in_the_budget_in_order() {
+        let mut queued = models(&["medium", "large", "tiny"]);
+        let jobs = strategy(8000, false).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(sources(&jobs), ["0.mp3"]);
+        assert_eq!(jobs[0].1.threads, Some(2));
+        assert_eq!(jobs[0].1.device.as_deref(), Some("cpu"));
+    }
+
+    #[test]
+    fn backfills_jobs_fitting_in_the_rest_of_the_budget() {
+        let mut queued = models(&["medium", "large", "tiny"]);
+        let jobs = strategy(8000, true).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(sources(&jobs), ["0.mp3", "2.mp3"]);
+        assert_eq!(sources(&Vec::from(queued)), ["1.mp3"]);
+    }
+
+    #[test]
+    fn runs_jobs_larger_than_the_budget_alone() {
+        let mut queued = models(&["large", "tiny"]);
+        let jobs = strategy(5000, false).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(sources(&jobs), ["0.mp3"]);
+    }
+
+    #[test]
+    fn limits_jobs_by_cores() {
+        let mut queued = models(&["tiny"; 6]);
+        let jobs = strategy(100000, false).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(jobs.len(), 4);
+    }
+}
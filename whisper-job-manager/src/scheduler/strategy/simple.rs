@@ -0,0 +1,178 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::scheduler::{job_spec::JobSpec, runner::RunningJob};
+
+use super::SchedulerStrategy;
+
+/// Device name meaning that whisper chooses the device, which will be "cuda" on devices with a compatible GPU, "cpu" if said device does not exist
+const AUTO_DEVICE: &str = "auto";
+
+/// Parameters of the simple strategy in the config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct SimpleStrategyParams {
+    /// The maximum number of jobs running at once
+    pub max_jobs: usize,
+    /// The model jobs run with, unless the job asks for one
+    pub default_model: String,
+    /// Devices that can only run one job at a time, like GPUs. `auto` lets whisper choose the device.
+    pub exclusive_devices: Vec<String>,
+    /// The device jobs run on when all exclusive devices are busy
+    pub shared_device: String,
+}
+
+impl Default for SimpleStrategyParams {
+    fn default() -> Self {
+        Self {
+            max_jobs: 2,
+            default_model: String::from("large"),
+            exclusive_devices: vec![String::from(AUTO_DEVICE)],
+            shared_device: String::from("cpu"),
+        }
+    }
+}
+
+/// Scheduler strategy that runs a fixed number of jobs at max, two by default. Each job is assigned to a free exclusive device if there is one, which
+/// by default is the GPU if one is available, and to the shared device otherwise, which by default is the CPU.
+#[derive(Debug)]
+pub struct SimpleSchedulerStrategy {
+    params: SimpleStrategyParams,
+}
+
+impl SimpleSchedulerStrategy {
+    /// Create the strategy from its parameters in the config.
+    pub fn from_params(params: serde_json::Value) -> Result<Box<dyn SchedulerStrategy>> {
+        let params: SimpleStrategyParams = super::parse_params(params)?;
+        Ok(Box::new(SimpleSchedulerStrategy::new(params)?))
+    }
+
+    /// Create the strategy from already parsed parameters, e.g. to be wrapped by another strategy.
+    pub fn new(params: SimpleStrategyParams) -> Result<Self> {
+        if params.max_jobs == 0 {
+            return Err(Error::msg("maxJobs must be greater than 0"));
+        }
+
+        Ok(SimpleSchedulerStrategy { params })
+    }
+}
+
+impl SchedulerStrategy for SimpleSchedulerStrategy {
+    fn select_queued_jobs_to_run(
+        &mut self,
+        queued_jobs: &mut VecDeque<(Uuid, JobSpec)>,
+        running_jobs: &HashMap<Uuid, RunningJob>,
+    ) -> Vec<(Uuid, JobSpec)> {
+        let mut jobs = Vec::with_capacity(self.params.max_jobs);
+        let mut busy_devices = busy_devices(running_jobs);
+
+        while running_jobs.len() + jobs.len() < self.params.max_jobs {
+            let Some(mut job) = queued_jobs.pop_front() else {
+                break;
+            };
+
+            self.update_job(&mut job, &mut busy_devices);
+            jobs.push(job);
+        }
+
+        jobs
+    }
+}
+
+impl SimpleSchedulerStrategy {
+    /// The maximum number of jobs running at once.
+    pub fn max_jobs(&self) -> usize {
+        self.params.max_jobs
+    }
+
+    /// Set the job to be for a free exclusive device or the shared device, and assign the default model if the job did not ask for one.
+    /// The device of the job is added to `busy_devices`.
+    pub fn update_job(&self, job: &mut (Uuid, JobSpec), busy_devices: &mut Vec<Option<String>>) {
+        let free_device = self
+            .params
+            .exclusive_devices
+            .iter()
+            .map(|d| device_for_spec(d))
+            .find(|d| !busy_devices.contains(d));
+
+        job.1.device = match free_device {
+            Some(d) => d,
+            None => device_for_spec(&self.params.shared_device),
+        };
+
+        if job.1.model.is_none() {
+            job.1.model = Some(self.params.default_model.clone());
+        }
+
+        busy_devices.push(job.1.device.clone());
+    }
+}
+
+/// The devices in use by running jobs.
+pub fn busy_devices(running_jobs: &HashMap<Uuid, RunningJob>) -> Vec<Option<String>> {
+    running_jobs
+        .values()
+        .map(|job| job.spec.device.clone())
+        .collect()
+}
+
+/// Convert a device name from the config to the device of a job spec, where no device lets whisper choose.
+fn device_for_spec(device: &str) -> Option<String> {
+    if device == AUTO_DEVICE {
+        None
+    } else {
+        Some(String::from(device))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::strategy::tests::{queue, sources};
+
+    use super::*;
+
+    fn strategy(max_jobs: usize) -> SimpleSchedulerStrategy {
+        SimpleSchedulerStrategy::new(SimpleStrategyParams {
+            max_jobs,
+            exclusive_devices: vec![String::from("cuda:0")],
+            ..SimpleStrategyParams::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn runs_jobs_in_order_up_to_the_maximum() {
+        let mut queued = queue(3, |_, _| {});
+        let jobs = strategy(2).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(sources(&jobs), ["0.mp3", "1.mp3"]);
+        assert_eq!(queued.len(), 1);
+    }
+
+    #[test]
+    fn assigns_exclusive_devices_then_the_shared_one() {
+        let mut queued = queue(2, |idx, spec| {
+            if idx == 1 {
+                spec.model = Some(String::from("tiny"));
+            }
+        });
+        let jobs = strategy(2).select_queued_jobs_to_run(&mut queued, &HashMap::new());
+
+        assert_eq!(jobs[0].1.device.as_deref(), Some("cuda:0"));
+        assert_eq!(jobs[0].1.model.as_deref(), Some("large"));
+        assert_eq!(jobs[1].1.device.as_deref(), Some("cpu"));
+        assert_eq!(jobs[1].1.model.as_deref(), Some("tiny"));
+    }
+
+    #[test]
+    fn rejects_zero_jobs() {
+        assert!(SimpleSchedulerStrategy::new(SimpleStrategyParams {
+            max_jobs: 0,
+            ..SimpleStrategyParams::default()
+        })
+        .is_err());
+    }
+}
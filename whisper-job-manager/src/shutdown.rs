@@ -0,0 +1,61 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::dev::ServerHandle;
+use tokio::{signal::unix::Signal, sync::Mutex};
+
+use crate::{config::ShutdownConfig, scheduler::Scheduler};
+
+/// How often to check whether the running jobs finished during the grace period
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait for `interrupt` or `terminate`, then shut down gracefully: stop accepting new jobs, give the running jobs the grace
+/// period to finish, stop the ones that did not, and finally stop the server. A second signal during the grace period
+/// stops the running jobs right away.
+pub async fn shutdown_on_signal(
+    mut interrupt: Signal,
+    mut terminate: Signal,
+    server: ServerHandle,
+    scheduler: Arc<Mutex<Scheduler>>,
+    config: ShutdownConfig,
+) {
+    tokio::select! {
+        _ = interrupt.recv() => log::info!("Received SIGINT, shutting down"),
+        _ = terminate.recv() => log::info!("Received SIGTERM, shutting down"),
+    }
+
+    scheduler.lock().await.start_draining();
+
+    let grace_period = Duration::from_secs(config.grace_period_secs);
+    tokio::select! {
+        drained = tokio::time::timeout(grace_period, wait_for_running_jobs(&scheduler)) => {
+            match drained {
+                Ok(()) => log::info!("All running jobs finished"),
+                Err(_) => log::warn!("Grace period of {:?} is over, stopping running jobs", grace_period),
+            }
+        }
+        _ = interrupt.recv() => log::warn!("Received SIGINT again, stopping running jobs"),
+        _ = terminate.recv() => log::warn!("Received SIGTERM again, stopping running jobs"),
+    }
+
+    scheduler
+        .lock()
+        .await
+        .shutdown(config.interrupted_jobs)
+        .await;
+
+    log::info!("Stopping server");
+    server.stop(true).await;
+}
+
+/// Wait until no job is running anymore.
+async fn wait_for_running_jobs(scheduler: &Mutex<Scheduler>) {
+    loop {
+        let running = scheduler.lock().await.running_job_count();
+        if running == 0 {
+            return;
+        }
+
+        log::debug!("Waiting for {} running jobs to finish", running);
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
+use uuid::Uuid;
+use whisper_job_manager_models::{job_metadata::JobMetadata, job_status::JobStatus};
+
+use crate::scheduler::job_spec::JobSpec;
+
+/// A snapshot of everything known about a job, as written to the job store.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobRecord {
+    pub id: Uuid,
+    pub spec: JobSpec,
+    pub status: JobStatus,
+    pub metadata: JobMetadata,
+}
+
+/// Durable store of jobs, implemented as an append-only journal of JSON lines. Every change to a job appends a new
+/// snapshot of the job, and the latest snapshot of a job wins when the journal is replayed.
+#[derive(Debug)]
+pub struct JobStore {
+    file: File,
+}
+
+impl JobStore {
+    /// Open the journal at the given path, creating it if it does not exist, and return the latest record of every
+    /// job in it. The journal is compacted so it only contains the returned records.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<JobRecord>)> {
+        let path = PathBuf::from(path.as_ref());
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let records = Self::replay(path.as_path())?;
+        Self::compact(path.as_path(), &records)?;
+
+        let file = OpenOptions::new().append(true).open(path.as_path())?;
+
+        log::info!("Loaded {} job records from {:?}", records.len(), path);
+
+        Ok((JobStore { file }, records))
+    }
+
+    /// Append the record to the journal.
+    pub fn append(&mut self, record: &JobRecord) -> Result<()> {
+        self.write(record)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// Move the journal to a dedicated thread that appends the records sent through the returned writer, so callers do not wait for
+    /// the disk. Records are written in the order they were sent, and synced to disk once per batch of records sent at the same time.
+    pub fn spawn_writer(self) -> Result<JobStoreWriter> {
+        let (messages, receiver) = mpsc::unbounded_channel();
+
+        std::thread::Builder::new()
+            .name(String::from("job-store"))
+            .spawn(move || self.write_messages(receiver))?;
+
+        Ok(JobStoreWriter { messages })
+    }
+
+    /// Write the records received until every writer is dropped.
+    fn write_messages(mut self, mut receiver: UnboundedReceiver<WriterMessage>) {
+        while let Some(message) = receiver.blocking_recv() {
+            let mut flushed = vec![];
+
+            let mut next = Some(message);
+            while let Some(message) = next {
+                match message {
+                    WriterMessage::Append(record) => {
+                        if let Err(e) = self.write(&record) {
+                            log::error!("Failed to persist job {}: {}", record.id, e);
+                        }
+                    }
+                    WriterMessage::Flush(done) => flushed.push(done),
+                }
+                next = receiver.try_recv().ok();
+            }
+
+            if let Err(e) = self.file.sync_data() {
+                log::error!("Failed to sync job store: {}", e);
+            }
+
+            for done in flushed {
+                // The sender may have stopped waiting, which is fine
+                let _ = done.send(());
+            }
+        }
+    }
+
+    /// Write the record to the journal, without syncing it to disk.
+    fn write(&mut self, record: &JobRecord) -> Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Read the journal at the given path, keeping the latest record of every job in order of creation.
+    fn replay(path: &Path) -> Result<Vec<JobRecord>> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        let reader = BufReader::new(File::open(path)?);
+        let mut records: HashMap<Uuid, JobRecord> = HashMap::new();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // A crash can leave a partially written last line, which is skipped rather than failing the whole load
+            match serde_json::from_str::<JobRecord>(&line) {
+                Ok(record) => {
+                    records.insert(record.id, record);
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Skipping invalid record on line {} of {:?}: {}",
+                        line_number + 1,
+                        path,
+                        e
+                    );
+                }
+            }
+        }
+
+        let mut records: Vec<JobRecord> = records.into_values().collect();
+        records.sort_by_key(|r| r.metadata.created_at);
+
+        Ok(records)
+    }
+
+    /// Rewrite the journal at the given path so it only contains the given records.
+    fn compact(path: &Path, records: &[JobRecord]) -> Result<()> {
+        let mut tmp_path = PathBuf::from(path);
+        tmp_path.set_extension("compact");
+
+        let mut tmp_file = File::create(tmp_path.as_path())?;
+        for record in records {
+            let mut line = serde_json::to_string(record)?;
+            line.push('\n');
+            tmp_file.write_all(line.as_bytes())?;
+        }
+        tmp_file.sync_all()?;
+
+        std::fs::rename(tmp_path.as_path(), path)?;
+
+        Ok(())
+    }
+}
+
+/// What the thread writing the job store is asked to do.
+enum WriterMessage {
+    /// Append the record to the journal
+    Append(Box<JobRecord>),
+    /// Report once every record sent before is synced to disk
+    Flush(oneshot::Sender<()>),
+}
+
+/// Handle to the thread writing the job store, see `JobStore::spawn_writer`.
+#[derive(Debug, Clone)]
+pub struct JobStoreWriter {
+    messages: UnboundedSender<WriterMessage>,
+}
+
+impl JobStoreWriter {
+    /// Queue the record to be appended to the journal.
+    pub fn append(&self, record: JobRecord) -> Result<()> {
+        self.messages
+            .send(WriterMessage::Append(Box::new(record)))
+            .map_err(|_| Error::msg("The job store writer stopped"))
+    }
+
+    /// Wait until every record queued before is synced to disk.
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.messages.send(WriterMessage::Flush(done)).is_ok() {
+            // An error means the writer stopped, and there is nothing left to wait for
+            let _ = flushed.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A journal path in a directory of its own, removed when the test ends.
+    struct TestJournal(PathBuf);
+
+    impl TestJournal {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("job-store-{}", Uuid::new_v4()));
+            TestJournal(dir.join("jobs.jsonl"))
+        }
+
+        fn lines(&self) -> usize {
+            std::fs::read_to_string(self.0.as_path())
+                .unwrap()
+                .lines()
+                .count()
+        }
+    }
+
+    impl Drop for TestJournal {
+        fn drop(&mut self) {
+            if let Some(dir) = self.0.parent() {
+                let _ = std::fs::remove_dir_all(dir);
+            }
+        }
+    }
+
+    fn record(filename: &str, status: JobStatus) -> JobRecord {
+        JobRecord {
+            id: Uuid::new_v4(),
+            spec: JobSpec::new(PathBuf::from(filename)),
+            status,
+            metadata: JobMetadata::init_for_queued_job(PathBuf::from(filename)),
+        }
+    }
+
+    #[test]
+    fn replays_the_latest_record_of_every_job() {
+        let journal = TestJournal::new();
+        let first = record("first.mp3", JobStatus::Queued);
+        let mut second = record("second.mp3", JobStatus::Queued);
+
+        let (mut store, records) = JobStore::open(journal.0.as_path()).unwrap();
+        assert!(records.is_empty());
+        store.append(&first).unwrap();
+        store.append(&second).unwrap();
+        second.status = JobStatus::Succeeded;
+        store.append(&second).unwrap();
+        drop(store);
+
+        let (_, records) = JobStore::open(journal.0.as_path()).unwrap();
+        let replayed: Vec<(Uuid, JobStatus)> =
+            records.into_iter().map(|r| (r.id, r.status)).collect();
+        assert_eq!(
+            replayed,
+            [
+                (first.id, JobStatus::Queued),
+                (second.id, JobStatus::Succeeded)
+            ]
+        );
+    }
+
+    #[test]
+    fn compacts_the_journal_when_opening_it() {
+        let journal = TestJournal::new();
+        let mut job = record("a.mp3", JobStatus::Queued);
+
+        let (mut store, _) = JobStore::open(journal.0.as_path()).unwrap();
+        for status in [JobStatus::Running, JobStatus::Succeeded] {
+            job.status = status;
+            store.append(&job).unwrap();
+        }
+        drop(store);
+        assert_eq!(journal.lines(), 2);
+
+        let (_, records) = JobStore::open(journal.0.as_path()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(journal.lines(), 1);
+    }
+
+    #[test]
+    fn skips_partially_written_records() {
+        let journal = TestJournal::new();
+        let job = record("a.mp3", JobStatus::Queued);
+
+        let (mut store, _) = JobStore::open(journal.0.as_path()).unwrap();
+        store.append(&job).unwrap();
+        store.file.write_all(b"{\"id\":\"").unwrap();
+        drop(store);
+
+        let (_, records) = JobStore::open(journal.0.as_path()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, job.id);
+    }
+
+    #[tokio::test]
+    async fn writes_records_sent_to_the_writer() {
+        let journal = TestJournal::new();
+        let jobs = [
+            record("a.mp3", JobStatus::Queued),
+            record("b.mp3", JobStatus::Queued),
+        ];
+
+        let (store, _) = JobStore::open(journal.0.as_path()).unwrap();
+        let writer = store.spawn_writer().unwrap();
+        for job in jobs.iter() {
+            writer.append(job.clone()).unwrap();
+        }
+        writer.flush().await;
+
+        let (_, records) = JobStore::open(journal.0.as_path()).unwrap();
+        let ids: Vec<Uuid> = records.into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, [jobs[0].id, jobs[1].id]);
+    }
+}
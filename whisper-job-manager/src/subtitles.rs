@@ -0,0 +1,274 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Error, Result};
+use whisper_job_manager_models::output_format::OutputFormat;
+
+/// Shift every timestamp of the transcript at the given path by `offset_secs`, so a transcript of part of a file matches the whole file. The
+/// shifted transcript replaces the file at once, so the file is never left partially written.
+pub async fn shift_file(path: &Path, format: OutputFormat, offset_secs: f64) -> Result<()> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Cannot read {:?}", path))?;
+    let shifted = shift_timestamps(&content, format, offset_secs)
+        .with_context(|| format!("Cannot shift the timestamps of {:?}", path))?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".shifting");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    if let Err(e) = tokio::fs::write(tmp_path.as_path(), shifted).await {
+        let _ = tokio::fs::remove_file(tmp_path.as_path()).await;
+        return Err(Error::from(e).context(format!("Cannot write {:?}", tmp_path)));
+    }
+
+    tokio::fs::rename(tmp_path.as_path(), path)
+        .await
+        .with_context(|| format!("Cannot replace {:?}", path))
+}
+
+/// Shift every timestamp of a transcript in the given format by `offset_secs`. Formats without timestamps are left as they are.
+pub fn shift_timestamps(content: &str, format: OutputFormat, offset_secs: f64) -> Result<String> {
+    match format {
+        OutputFormat::Srt => Ok(shift_cue_lines(content, ',', true, offset_secs)),
+        OutputFormat::Vtt => Ok(shift_cue_lines(content, '.', false, offset_secs)),
+        OutputFormat::Tsv => shift_tsv(content, offset_secs),
+        OutputFormat::Json => shift_json(content, offset_secs),
+        OutputFormat::Txt | OutputFormat::All => Ok(content.to_string()),
+    }
+}
+
+/// Shift the timing lines of SRT and VTT files, like `00:01:02,500 --> 00:01:04,000`, keeping anything after the end time. Other lines are
+/// left as they are.
+fn shift_cue_lines(
+    content: &str,
+    separator: char,
+    always_include_hours: bool,
+    offset_secs: f64,
+) -> String {
+    let mut shifted = String::with_capacity(content.len());
+
+    for line in content.split_inclusive('\n') {
+        let Some((start, rest)) = line.split_once(" --> ") else {
+            shifted.push_str(line);
+            continue;
+        };
+        let end_len = rest.find(|c: char| c.is_whitespace()).unwrap_or(rest.len());
+        let (end, rest) = rest.split_at(end_len);
+
+        let (Some(start), Some(end)) = (
+            parse_timestamp(start.trim(), separator),
+            parse_timestamp(end, separator),
+        ) else {
+            // Text that happens to contain an arrow
+            shifted.push_str(line);
+            continue;
+        };
+
+        shifted.push_str(&format_timestamp(
+            start + offset_secs,
+            separator,
+            always_include_hours,
+        ));
+        shifted.push_str(" --> ");
+        shifted.push_str(&format_timestamp(
+            end + offset_secs,
+            separator,
+            always_include_hours,
+        ));
+        shifted.push_str(rest);
+    }
+
+    shifted
+}
+
+/// Shift the start and end columns of a TSV file, in milliseconds, leaving its header as it is.
+fn shift_tsv(content: &str, offset_secs: f64) -> Result<String> {
+    let offset_millis = (offset_secs * 1000.0).round() as i64;
+    let mut shifted = String::with_capacity(content.len());
+
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let mut columns = line.splitn(3, '\t');
+        let (Some(start), Some(end), Some(text)) = (columns.next(), columns.next(), columns.next())
+        else {
+            shifted.push_str(line);
+            continue;
+        };
+
+        let (Ok(start), Ok(end)) = (start.parse::<i64>(), end.parse::<i64>()) else {
+            if i == 0 {
+                // The header
+                shifted.push_str(line);
+                continue;
+            }
+            return Err(Error::msg(format!("Invalid TSV row {:?}", line)));
+        };
+
+        shifted.push_str(&format!(
+            "{}\t{}\t{}",
+            start + offset_millis,
+            end + offset_millis,
+            text
+        ));
+    }
+
+    Ok(shifted)
+}
+
+/// Shift the start and end of every segment of a JSON transcript, and of the words of the segments if there are any.
+fn shift_json(content: &str, offset_secs: f64) -> Result<String> {
+    let mut transcript: serde_json::Value = serde_json::from_str(content)?;
+
+    let shift = |value: &mut serde_json::Value| {
+        for key in ["start", "end"] {
+            if let Some(time) = value.get(key).and_then(|t| t.as_f64()) {
+                value[key] = serde_json::json!(time + offset_secs);
+            }
+        }
+    };
+
+    if let Some(segments) = transcript
+        .get_mut("segments")
+        .and_then(|s| s.as_array_mut())
+    {
+        for segment in segments {
+            shift(segment);
+            if let Some(words) = segment.get_mut("words").and_then(|w| w.as_array_mut()) {
+                words.iter_mut().for_each(shift);
+            }
+        }
+    }
+
+    Ok(serde_json::to_string(&transcript)?)
+}
+
+/// Parse a timestamp like `01:02:03,500` or `02:03.500`, in seconds.
+fn parse_timestamp(timestamp: &str, separator: char) -> Option<f64> {
+    let (time, millis) = timestamp.split_once(separator)?;
+    let millis: u64 = millis.parse().ok()?;
+
+    let mut secs: u64 = 0;
+    for part in time.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+
+    Some(secs as f64 + millis as f64 / 1000.0)
+}
+
+/// Format a time in seconds as `hh:mm:ss` followed by the separator and the milliseconds. Hours are left out when there are none,
+/// unless they are always included.
+fn format_timestamp(secs: f64, separator: char, always_include_hours: bool) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+    let (hours, minutes, seconds, millis) = (
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000,
+    );
+
+    if always_include_hours || hours > 0 {
+        format!(
+            "{:02}:{:02}:{:02}{}{:03}",
+            hours, minutes, seconds, separator, millis
+        )
+    } else {
+        format!("{:02}:{:02}{}{:03}", minutes, seconds, separator, millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shifts_srt_cues() {
+        let srt = "1\n00:00:01,500 --> 00:00:03,000\nHello --> there\n\n2\n00:59:59,000 --> 01:00:01,250\nBye\n";
+        let shifted = shift_timestamps(srt, OutputFormat::Srt, 90.0).unwrap();
+        assert_eq!(
+            shifted,
+            "1\n00:01:31,500 --> 00:01:33,000\nHello --> there\n\n2\n01:01:29,000 --> 01:01:31,250\nBye\n"
+        );
+    }
+
+    #[test]
+    fn shifts_vtt_cues_keeping_settings() {
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:02.500 align:start\nHello\n\n59:59.500 --> 01:00:00.000\nBye\n";
+        let shifted = shift_timestamps(vtt, OutputFormat::Vtt, 1.0).unwrap();
+        assert_eq!(
+            shifted,
+            "WEBVTT\n\n00:02.000 --> 00:03.500 align:start\nHello\n\n01:00:00.500 --> 01:00:01.000\nBye\n"
+        );
+    }
+
+    #[test]
+    fn shifts_tsv_rows_keeping_header() {
+        let tsv = "start\tend\ttext\n0\t1500\tHello\n1500\t3000\tThere\tagain\n";
+        let shifted = shift_timestamps(tsv, OutputFormat::Tsv, 2.25).unwrap();
+        assert_eq!(
+            shifted,
+            "start\tend\ttext\n2250\t3750\tHello\n3750\t5250\tThere\tagain\n"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_tsv_rows() {
+        let tsv = "start\tend\ttext\nzero\t1500\tHello\n";
+        assert!(shift_timestamps(tsv, OutputFormat::Tsv, 1.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn leaves_files_that_cannot_be_shifted_untouched() {
+        let path = std::env::temp_dir().join(format!("{}.tsv", uuid::Uuid::new_v4()));
+        let tsv = "start\tend\ttext\nzero\t1500\tHello\n";
+        std::fs::write(path.as_path(), tsv).unwrap();
+
+        assert!(shift_file(path.as_path(), OutputFormat::Tsv, 1.0)
+            .await
+            .is_err());
+        assert_eq!(std::fs::read_to_string(path.as_path()).unwrap(), tsv);
+
+        std::fs::write(path.as_path(), "start\tend\ttext\n0\t1500\tHello\n").unwrap();
+        shift_file(path.as_path(), OutputFormat::Tsv, 1.0)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path.as_path()).unwrap(),
+            "start\tend\ttext\n1000\t2500\tHello\n"
+        );
+
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".shifting");
+        assert!(!PathBuf::from(tmp_path).exists());
+        std::fs::remove_file(path.as_path()).unwrap();
+    }
+
+    #[test]
+    fn shifts_json_segments_and_words() {
+        let json = r#"{"text":"Hi","segments":[{"start":0.5,"end":1.0,"words":[{"start":0.5,"end":0.75}]}]}"#;
+        let shifted: serde_json::Value =
+            serde_json::from_str(&shift_timestamps(json, OutputFormat::Json, 10.0).unwrap())
+                .unwrap();
+        assert_eq!(
+            shifted,
+            serde_json::json!({
+                "text": "Hi",
+                "segments": [{"start": 10.5, "end": 11.0, "words": [{"start": 10.5, "end": 10.75}]}]
+            })
+        );
+    }
+
+    #[test]
+    fn leaves_txt_as_is() {
+        let txt = "00:00:01,500 --> 00:00:03,000\n";
+        assert_eq!(shift_timestamps(txt, OutputFormat::Txt, 5.0).unwrap(), txt);
+    }
+
+    #[test]
+    fn parses_and_formats_timestamps() {
+        assert_eq!(parse_timestamp("01:02:03,500", ','), Some(3723.5));
+        assert_eq!(parse_timestamp("02:03.250", '.'), Some(123.25));
+        assert_eq!(parse_timestamp("02:03", '.'), None);
+        assert_eq!(format_timestamp(3723.5, ',', true), "01:02:03,500");
+        assert_eq!(format_timestamp(123.25, '.', false), "02:03.250");
+        assert_eq!(format_timestamp(-1.0, '.', false), "00:00.000");
+    }
+}
@@ -0,0 +1,252 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use sha2::Sha256;
+use tokio::{io::AsyncWriteExt, sync::mpsc::UnboundedReceiver};
+use uuid::Uuid;
+use whisper_job_manager_models::{
+    job_metadata::JobMetadata,
+    job_status::JobStatus,
+    webhook::{
+        ArtifactLink, WebhookDelivery, WebhookPayload, DELIVERY_HEADER, EVENT_HEADER,
+        JOB_FINISHED_EVENT, SIGNATURE_HEADER,
+    },
+};
+
+use crate::{
+    backend::TranscriptionBackend,
+    config::{Config, WebhookConfig},
+    routes::get_artifacts::list_artifacts,
+};
+
+/// A job that just finished, whose outcome is POSTed to the webhooks.
+#[derive(Debug)]
+pub struct FinishedJob {
+    pub id: Uuid,
+    pub status: JobStatus,
+    pub metadata: JobMetadata,
+    /// The URL the job asked to be called back at, if any
+    pub callback_url: Option<String>,
+}
+
+/// Call the webhooks of every job sent by the scheduler once it finishes. Each webhook is delivered by its own task, so a slow webhook does not
+/// hold back the others. Deliveries still waiting to be retried are lost when the server stops.
+pub async fn run_dispatcher(
+    mut jobs: UnboundedReceiver<FinishedJob>,
+    config: Arc<Config>,
+    backend: Arc<dyn TranscriptionBackend>,
+) {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.webhooks.timeout_secs))
+        // A redirected POST would be sent again as a GET, so a redirect is treated as a failed delivery instead
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!(
+                "Could not create the webhook client, webhooks are disabled: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    let public_url = config.public_url();
+
+    while let Some(job) = jobs.recv().await {
+        let urls: Vec<String> = config
+            .webhooks
+            .urls
+            .iter()
+            .cloned()
+            .chain(job.callback_url.clone())
+            .collect();
+
+        if urls.is_empty() {
+            continue;
+        }
+
+        let payload = build_payload(job, backend.as_ref(), &public_url);
+        let body = match serde_json::to_vec(&payload) {
+            Ok(b) => b,
+            Err(e) => {
+                log::error!(
+                    "Could not serialize webhook payload of job {}: {}",
+                    payload.uuid,
+                    e
+                );
+                continue;
+            }
+        };
+        let signature = config.webhooks.secret.as_deref().map(|s| sign(s, &body));
+        let body = Arc::new(body);
+
+        for url in urls {
+            actix_web::rt::spawn(deliver(
+                client.clone(),
+                config.webhooks.clone(),
+                payload.uuid,
+                url,
+                body.clone(),
+                signature.clone(),
+            ));
+        }
+    }
+
+    log::info!("The scheduler is gone, stopping webhook dispatcher");
+}
+
+/// The payload telling webhooks how the job finished, with links to download the files it produced.
+fn build_payload(
+    job: FinishedJob,
+    backend: &dyn TranscriptionBackend,
+    public_url: &str,
+) -> WebhookPayload {
+    let artifacts = list_artifacts(backend, job.id, job.metadata.output_formats.clone())
+        .into_iter()
+        .map(|artifact| ArtifactLink {
+            url: format!(
+                "{}/v2/jobs/{}/artifacts/{}",
+                public_url, job.id, artifact.format
+            ),
+            artifact,
+        })
+        .collect();
+
+    WebhookPayload {
+        uuid: job.id,
+        status: job.status,
+        metadata: job.metadata,
+        artifacts,
+    }
+}
+
+/// The signature of the body with the given secret, as sent in the signature header.
+fn sign(secret: &str, body: &[u8]) -> String {
+    // Unwrap ok because HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// POST the body to the webhook until it accepts it, it rejects it for good, or the attempts run out. Every attempt is recorded in the
+/// delivery log of the job.
+async fn deliver(
+    client: reqwest::Client,
+    config: WebhookConfig,
+    job_id: Uuid,
+    url: String,
+    body: Arc<Vec<u8>>,
+    signature: Option<String>,
+) {
+    let delivery = Uuid::new_v4();
+
+    for attempt in 1..=config.max_attempts {
+        let mut request = client
+            .post(url.as_str())
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, JOB_FINISHED_EVENT)
+            .header(DELIVERY_HEADER, delivery.to_string())
+            .body(body.as_ref().clone());
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature.as_str());
+        }
+
+        let (status_code, error, retryable) = match request.send().await {
+            Ok(resp) if resp.status().is_success() => (Some(resp.status()), None, false),
+            Ok(resp) => {
+                let status = resp.status();
+                let retryable = status.is_server_error()
+                    || status == StatusCode::TOO_MANY_REQUESTS
+                    || status == StatusCode::REQUEST_TIMEOUT;
+                (
+                    Some(status),
+                    Some(format!("Responded with {}", status)),
+                    retryable,
+                )
+            }
+            Err(e) => (None, Some(e.to_string()), true),
+        };
+
+        let record = WebhookDelivery {
+            uuid: job_id,
+            delivery,
+            url: url.clone(),
+            attempt,
+            at: chrono::offset::Utc::now(),
+            status_code: status_code.map(|s| s.as_u16()),
+            delivered: error.is_none(),
+            error,
+        };
+
+        if let Err(e) = append_delivery(&config.delivery_log_path, &record).await {
+            log::error!("Could not record webhook delivery of job {}: {}", job_id, e);
+        }
+
+        let Some(error) = record.error else {
+            log::info!("Delivered webhook of job {} to {}", job_id, url);
+            return;
+        };
+
+        if !retryable || attempt == config.max_attempts {
+            log::warn!(
+                "Giving up on webhook of job {} to {} after {} attempts: {}",
+                job_id,
+                url,
+                attempt,
+                error
+            );
+            return;
+        }
+
+        let backoff = config.backoff(attempt);
+        log::warn!(
+            "Webhook of job {} to {} failed, retrying in {:?}: {}",
+            job_id,
+            url,
+            backoff,
+            error
+        );
+        tokio::time::sleep(backoff).await;
+    }
+}
+
+/// Append the attempt to the delivery log.
+async fn append_delivery(log_path: &str, delivery: &WebhookDelivery) -> Result<()> {
+    let mut line = serde_json::to_string(delivery)?;
+    line.push('\n');
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+
+    Ok(())
+}
+
+/// Read the attempts at calling the webhooks of the job from the delivery log, oldest first. A job whose webhooks were never called has no
+/// deliveries.
+pub async fn read_deliveries(log_path: &str, job_id: Uuid) -> Result<Vec<WebhookDelivery>> {
+    let content = match tokio::fs::read_to_string(log_path).await {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut deliveries = vec![];
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        // A crash can leave a partially written line, which is skipped rather than failing the whole read
+        match serde_json::from_str::<WebhookDelivery>(line) {
+            Ok(d) if d.uuid == job_id => deliveries.push(d),
+            Ok(_) => {}
+            Err(e) => log::warn!("Skipping invalid webhook delivery in {}: {}", log_path, e),
+        }
+    }
+
+    Ok(deliveries)
+}
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+use whisper_job_manager_models::output_format::OutputFormat;
+
+use crate::constants::TMP_DIR;
+
+pub const STDOUT_FILE: &str = "out.txt";
+pub const STDERR_FILE: &str = "err.txt";
+/// Directory of the workspace holding the uploaded file to transcribe, apart from the files the job writes
+pub const UPLOAD_DIR: &str = "upload";
+
+/// Get the path of the workspace directory of the job with the given UUID.
+pub fn workspace_path(uuid: Uuid) -> PathBuf {
+    let mut workspace = TMP_DIR.clone();
+    workspace.push(uuid.to_string());
+    workspace
+}
+
+/// Get the path of the given file in the workspace of the job with the given UUID.
+pub fn workspace_file(uuid: Uuid, filename: &str) -> PathBuf {
+    let mut path = workspace_path(uuid);
+    path.push(filename);
+    path
+}
+
+/// Find a file with the given extension in the directory, if there is one.
+pub fn find_file_with_extension<P: AsRef<Path>>(dir: P, extension: &str) -> Option<PathBuf> {
+    let files = std::fs::read_dir(dir.as_ref()).ok()?;
+
+    files
+        .filter_map(|f| f.ok())
+        .map(|f| f.path())
+        .find(|p| p.extension().is_some_and(|e| e == extension))
+}
+
+/// Find the file of the given format that whisper wrote in the workspace of the job with the given UUID, if there is one.
+pub fn find_artifact(uuid: Uuid, format: OutputFormat) -> Option<PathBuf> {
+    let files = std::fs::read_dir(workspace_path(uuid)).ok()?;
+
+    // The output of the process is written to text files as well
+    files
+        .filter_map(|f| f.ok())
+        .map(|f| f.path())
+        .filter(|p| {
+            p.file_name()
+                .is_some_and(|n| n != STDOUT_FILE && n != STDERR_FILE)
+        })
+        .find(|p| p.extension().is_some_and(|e| e == format.as_str()))
+}
+
+/// Get the path an uploaded file with the given name is saved to, in the workspace of the job with the given UUID.
+pub fn upload_path(uuid: Uuid, filename: &Path) -> PathBuf {
+    let mut path = workspace_file(uuid, UPLOAD_DIR);
+    path.push(filename);
+    path
+}
+
+/// Get the path of the WAV file the given source is prepared to before it is transcribed, in the workspace of the job with the given UUID.
+/// The file is named after the source, like the files the job writes.
+pub fn preprocessed_path(uuid: Uuid, source: &Path) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("source"));
+    workspace_file(uuid, &format!("{}.wav", stem))
+}
+
+/// Create the workspace directory of a job, along with empty stdout and stderr files.
+pub async fn setup_workspace(uuid: Uuid) -> tokio::io::Result<PathBuf> {
+    // Create directory for this job
+    let workspace = workspace_path(uuid);
+    tokio::fs::create_dir_all(workspace.as_path()).await?;
+
+    // Create stdio
+    tokio::fs::File::create(workspace_file(uuid, STDOUT_FILE)).await?;
+
+    // Create stderr
+    tokio::fs::File::create(workspace_file(uuid, STDERR_FILE)).await?;
+
+    Ok(workspace)
+}
+
+/// Remove the workspace directory and everything in it.
+pub async fn cleanup_workspace(workspace_path: PathBuf) {
+    if let Err(e) = tokio::fs::remove_dir_all(workspace_path.as_path()).await {
+        log::error!("Failed to remove {:?}: {}", workspace_path, e);
+    }
+}
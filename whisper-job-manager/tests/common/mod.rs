@@ -0,0 +1,237 @@
+//! Runs the server as a separate process for integration tests, with the fake backend standing in for whisper.
+
+// Every test uses only some of the helpers
+#![allow(dead_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::Duration,
+};
+
+use uuid::Uuid;
+use whisper_job_manager_models::{job_status::JobStatus, GetJobLogsResponse, GetStatusResponse};
+
+/// How long the server has to start
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a job has to reach a status
+const STATUS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often the server is polled while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A server running in a directory of its own, which is stopped and removed when dropped.
+pub struct TestServer {
+    process: Child,
+    dir: PathBuf,
+    pub url: String,
+    pub client: reqwest::Client,
+}
+
+impl TestServer {
+    /// Start a server whose config is the given one on top of a config using the fake backend. The storage directory has an `a.mp3`
+    /// file to transcribe.
+    pub async fn start(config: serde_json::Value) -> Self {
+        let dir = std::env::temp_dir().join(format!("whisper-job-manager-test-{}", Uuid::new_v4()));
+        let media = dir.join("media");
+        std::fs::create_dir_all(media.as_path()).unwrap();
+        std::fs::write(media.join("a.mp3"), b"not really audio").unwrap();
+
+        let (process, url) = spawn(dir.as_path(), config);
+        let server = TestServer {
+            process,
+            dir,
+            url,
+            client: reqwest::Client::new(),
+        };
+        server.wait_until_ready().await;
+        server
+    }
+
+    /// Kill the server as if it crashed, and start it again in the same directory with the given config. The processes of its jobs are
+    /// killed after it, since the server cannot kill them anymore.
+    pub async fn restart(&mut self, config: serde_json::Value) {
+        let children = Command::new("pgrep")
+            .args(["-P", &self.process.id().to_string()])
+            .output()
+            .unwrap();
+        self.process.kill().unwrap();
+        self.process.wait().unwrap();
+        for pid in String::from_utf8_lossy(&children.stdout).split_whitespace() {
+            let _ = Command::new("kill").args(["-9", pid]).status();
+        }
+
+        let (process, url) = spawn(self.dir.as_path(), config);
+        self.process = process;
+        self.url = url;
+        self.wait_until_ready().await;
+    }
+
+    async fn wait_until_ready(&self) {
+        let deadline = tokio::time::Instant::now() + START_TIMEOUT;
+        while tokio::time::Instant::now() < deadline {
+            if self.get("/v2/jobs").await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        panic!(
+            "The server did not start, see {:?}",
+            self.dir.join("server.log")
+        );
+    }
+
+    pub async fn get(&self, path: &str) -> reqwest::Result<reqwest::Response> {
+        self.client
+            .get(format!("{}{}", self.url, path))
+            .send()
+            .await
+    }
+
+    /// GET the path and parse the JSON body, expecting the given status code.
+    pub async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str, status: u16) -> T {
+        let response = self.get(path).await.unwrap();
+        assert_eq!(response.status().as_u16(), status, "GET {}", path);
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap()
+    }
+
+    /// POST the body as JSON to the path.
+    pub async fn post_json(&self, path: &str, body: serde_json::Value) -> reqwest::Response {
+        self.client
+            .post(format!("{}{}", self.url, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .unwrap()
+    }
+
+    /// Queue a job with the given body, and return its UUID.
+    pub async fn create_job(&self, body: serde_json::Value) -> Uuid {
+        let response = self.post_json("/v2/jobs", body).await;
+        assert_eq!(response.status().as_u16(), 201);
+
+        let job: GetStatusResponse =
+            serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+        job.uuid
+    }
+
+    /// Wait until the job has a status the predicate accepts, and return the job.
+    pub async fn wait_for_job(
+        &self,
+        id: Uuid,
+        predicate: impl Fn(&JobStatus) -> bool,
+    ) -> GetStatusResponse {
+        let deadline = tokio::time::Instant::now() + STATUS_TIMEOUT;
+        loop {
+            let job: GetStatusResponse = self.get_json(&format!("/v2/jobs/{}", id), 200).await;
+            if predicate(&job.status) {
+                return job;
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "Job {} is still {:?}",
+                id,
+                job.status
+            );
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Wait until the logs of the job on the given stream are accepted by the predicate.
+    pub async fn wait_for_logs(&self, id: Uuid, stream: &str, predicate: impl Fn(&str) -> bool) {
+        let deadline = tokio::time::Instant::now() + STATUS_TIMEOUT;
+        loop {
+            let logs: GetJobLogsResponse = self
+                .get_json(&format!("/getJobLogs?uuid={}&stream={}", id, stream), 200)
+                .await;
+            if logs.logs.iter().any(|l| predicate(&l.content)) {
+                return;
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "Job {} did not log what was expected on {}",
+                id,
+                stream
+            );
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = std::fs::remove_dir_all(self.dir.as_path());
+    }
+}
+
+/// Run the server in the directory, with the given config on top of a config using the fake backend. Returns the process and the URL
+/// of the server.
+fn spawn(dir: &Path, config: serde_json::Value) -> (Child, String) {
+    let port = free_port();
+    let mut full_config = serde_json::json!({
+        "videoStoragePath": dir.join("media"),
+        "host": "127.0.0.1",
+        "port": port,
+        "scheduler": { "tickMillis": 200 },
+        "backend": {
+            "name": "fake",
+            "params": { "durationSecs": 0.5, "segments": 2, "segmentSecs": 1.0 }
+        },
+    });
+    for (key, value) in config.as_object().unwrap() {
+        full_config[key] = value.clone();
+    }
+    let config_path = dir.join("config.json");
+    std::fs::write(config_path.as_path(), full_config.to_string()).unwrap();
+
+    let log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("server.log"))
+        .unwrap();
+    let process = Command::new(env!("CARGO_BIN_EXE_whisper-job-manager"))
+        .arg(config_path.as_path())
+        .current_dir(dir)
+        .stdout(Stdio::null())
+        .stderr(log)
+        .spawn()
+        .unwrap();
+
+    (process, format!("http://127.0.0.1:{}", port))
+}
+
+/// An executable shell script, standing in for a program the server runs. It is removed when dropped.
+pub struct Script {
+    pub path: PathBuf,
+}
+
+impl Script {
+    pub fn new(content: &str) -> Self {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path =
+            std::env::temp_dir().join(format!("whisper-job-manager-script-{}", Uuid::new_v4()));
+        std::fs::write(path.as_path(), content).unwrap();
+        std::fs::set_permissions(path.as_path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+        Script { path }
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(self.path.as_path());
+    }
+}
+
+/// A port nothing listens on right now.
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
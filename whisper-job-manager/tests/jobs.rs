@@ -0,0 +1,187 @@
+//! Runs jobs end-to-end with the fake backend.
+
+mod common;
+
+use common::{Script, TestServer};
+use whisper_job_manager_models::{job_status::JobStatus, GetJobLogsResponse};
+
+#[tokio::test]
+async fn transcribes_a_file() {
+    let server = TestServer::start(serde_json::json!({})).await;
+
+    let id = server
+        .create_job(serde_json::json!({ "path": "a.mp3", "output_formats": ["srt", "vtt"] }))
+        .await;
+    let job = server.wait_for_job(id, JobStatus::is_finished).await;
+
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(job.metadata.attempts, 1);
+
+    for (format, timing) in [
+        ("srt", "00:00:00,000 --> 00:00:01,000"),
+        ("vtt", "00:00.000 --> 00:01.000"),
+    ] {
+        let response = server
+            .get(&format!("/v2/jobs/{}/artifacts/{}", id, format))
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+
+        let transcript = response.text().await.unwrap();
+        assert!(transcript.contains(timing), "{}", transcript);
+        assert!(
+            transcript.contains("Segment 2 transcribed from a.mp3"),
+            "{}",
+            transcript
+        );
+    }
+
+    let logs: GetJobLogsResponse = server
+        .get_json(&format!("/getJobLogs?uuid={}&stream=stdout", id), 200)
+        .await;
+    assert!(logs.logs[0].content.contains("[00:01.000 --> 00:02.000]"));
+}
+
+#[tokio::test]
+async fn retries_failed_jobs_keeping_the_logs_of_every_attempt() {
+    let server = TestServer::start(serde_json::json!({
+        "backend": { "name": "fake", "params": { "durationSecs": 0.1, "exitCode": 3 } },
+        "retry": { "maxAttempts": 2, "initialBackoffSecs": 1 },
+    }))
+    .await;
+
+    let id = server
+        .create_job(serde_json::json!({ "path": "a.mp3" }))
+        .await;
+    let job = server.wait_for_job(id, JobStatus::is_finished).await;
+
+    let JobStatus::Failed {
+        reason: Some(reason),
+    } = job.status
+    else {
+        panic!("Job {} did not fail: {:?}", id, job.status);
+    };
+    assert!(reason.contains("after 2 attempts"), "{}", reason);
+    assert_eq!(job.metadata.attempts, 2);
+
+    let logs: GetJobLogsResponse = server
+        .get_json(&format!("/getJobLogs?uuid={}&stream=stderr", id), 200)
+        .await;
+    let stderr = &logs.logs[0].content;
+    assert_eq!(stderr.matches("RuntimeError").count(), 2, "{}", stderr);
+    assert!(stderr.contains("===== Attempt 2 ====="), "{}", stderr);
+}
+
+#[tokio::test]
+async fn requeues_interrupted_jobs_keeping_the_logs_of_the_interrupted_attempt() {
+    let mut server = TestServer::start(serde_json::json!({
+        "backend": { "name": "fake", "params": { "durationSecs": 0.1, "hang": true } },
+    }))
+    .await;
+
+    let id = server
+        .create_job(serde_json::json!({ "path": "a.mp3" }))
+        .await;
+    server
+        .wait_for_logs(id, "stderr", |logs| logs.contains("Hanging"))
+        .await;
+
+    server
+        .restart(serde_json::json!({
+            "backend": { "name": "fake", "params": { "durationSecs": 0.1 } },
+            "recovery": { "interruptedJobs": "requeue" },
+        }))
+        .await;
+
+    let job = server.wait_for_job(id, JobStatus::is_finished).await;
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(job.metadata.attempts, 2);
+
+    let logs: GetJobLogsResponse = server
+        .get_json(&format!("/getJobLogs?uuid={}&stream=stderr", id), 200)
+        .await;
+    let stderr = &logs.logs[0].content;
+    assert!(stderr.contains("Hanging"), "{}", stderr);
+    assert!(stderr.contains("===== Attempt 2 ====="), "{}", stderr);
+}
+
+#[tokio::test]
+async fn has_no_artifacts_for_canceled_jobs() {
+    let server = TestServer::start(serde_json::json!({
+        "backend": { "name": "fake", "params": { "hang": true } },
+    }))
+    .await;
+
+    let id = server
+        .create_job(serde_json::json!({ "path": "a.mp3" }))
+        .await;
+    server.wait_for_job(id, |s| *s == JobStatus::Running).await;
+
+    let response = server
+        .client
+        .delete(format!("{}/v2/jobs/{}", server.url, id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 204);
+
+    let job = server.wait_for_job(id, JobStatus::is_finished).await;
+    assert_eq!(job.status, JobStatus::Canceled);
+
+    let response = server
+        .get(&format!("/v2/jobs/{}/artifacts/srt", id))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn has_no_artifacts_for_jobs_whose_timestamps_could_not_be_shifted() {
+    // Stands in for ffmpeg, copying the input to the output, which is the last argument
+    let ffmpeg = Script::new(
+        "#!/bin/sh\nwhile [ $# -gt 1 ]; do\n  [ \"$1\" = \"-i\" ] && input=\"$2\"\n  shift\ndone\ncp \"$input\" \"$1\"\n",
+    );
+    let server = TestServer::start(serde_json::json!({
+        "backend": { "name": "fake", "params": { "durationSecs": 0.1, "malformedTsv": true } },
+        "preprocessing": { "command": ffmpeg.path },
+    }))
+    .await;
+
+    let id = server
+        .create_job(serde_json::json!({
+            "path": "a.mp3",
+            "output_formats": ["srt", "tsv"],
+            "start_secs": 60.0,
+        }))
+        .await;
+    let job = server.wait_for_job(id, JobStatus::is_finished).await;
+
+    let JobStatus::Failed {
+        reason: Some(reason),
+    } = job.status
+    else {
+        panic!("Job {} did not fail: {:?}", id, job.status);
+    };
+    assert!(reason.contains("Could not shift"), "{}", reason);
+
+    // The SRT transcript was shifted before the TSV one failed, but is removed all the same
+    for format in ["srt", "tsv"] {
+        let response = server
+            .get(&format!("/v2/jobs/{}/artifacts/{}", id, format))
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 404, "{}", format);
+    }
+}
+
+#[tokio::test]
+async fn rejects_files_outside_the_storage_directory() {
+    let server = TestServer::start(serde_json::json!({})).await;
+
+    for path in ["missing.mp3", "../config.json"] {
+        let response = server
+            .post_json("/v2/jobs", serde_json::json!({ "path": path }))
+            .await;
+        assert_eq!(response.status().as_u16(), 422, "{}", path);
+    }
+}
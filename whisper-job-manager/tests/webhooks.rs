@@ -0,0 +1,254 @@
+//! Calls webhooks on a local listener standing in for the receiver.
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::TestServer;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    sync::mpsc,
+};
+use whisper_job_manager_models::{
+    job_status::JobStatus,
+    webhook::{
+        GetWebhookDeliveriesResponse, WebhookPayload, DELIVERY_HEADER, EVENT_HEADER,
+        JOB_FINISHED_EVENT, SIGNATURE_HEADER,
+    },
+};
+
+const SECRET: &str = "test-secret";
+
+/// A request received by the listener.
+struct ReceivedRequest {
+    /// The headers, by lowercase name
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// Listen for webhook calls, responding with the given status codes in order and with `200` once they run out. Every request received
+/// is sent to the returned channel.
+async fn listen(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<ReceivedRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut statuses = statuses.into_iter();
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut headers = HashMap::new();
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            loop {
+                line.clear();
+                reader.read_line(&mut line).await.unwrap();
+                let Some((name, value)) = line.trim_end().split_once(':') else {
+                    break;
+                };
+                headers.insert(name.to_lowercase(), value.trim().to_string());
+            }
+
+            let length = headers
+                .get("content-length")
+                .map_or(0, |l| l.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await.unwrap();
+
+            let status = statuses.next().unwrap_or(200);
+            let response = format!(
+                "HTTP/1.1 {} Test\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                status
+            );
+            reader
+                .get_mut()
+                .write_all(response.as_bytes())
+                .await
+                .unwrap();
+
+            if requests.send(ReceivedRequest { headers, body }).is_err() {
+                return;
+            }
+        }
+    });
+
+    (url, received)
+}
+
+fn signature(body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// GET the deliveries of a job from the path until there are as many as expected, or the server took too long to record them.
+async fn wait_for_deliveries(
+    server: &TestServer,
+    path: &str,
+    count: usize,
+) -> GetWebhookDeliveriesResponse {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(30);
+    loop {
+        let response: GetWebhookDeliveriesResponse = server.get_json(path, 200).await;
+        if response.deliveries.len() >= count || tokio::time::Instant::now() > deadline {
+            return response;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test]
+async fn retries_signed_deliveries_until_they_are_accepted() {
+    let (url, mut received) = listen(vec![500, 429]).await;
+    let server = TestServer::start(serde_json::json!({
+        "webhooks": {
+            "urls": [url],
+            "secret": SECRET,
+            "maxAttempts": 4,
+            "initialBackoffSecs": 1,
+            "backoffMultiplier": 1.0,
+        },
+    }))
+    .await;
+
+    let id = server
+        .create_job(serde_json::json!({ "path": "a.mp3" }))
+        .await;
+    server.wait_for_job(id, JobStatus::is_finished).await;
+
+    let mut requests = vec![];
+    for _ in 0..3 {
+        let request = tokio::time::timeout(std::time::Duration::from_secs(30), received.recv())
+            .await
+            .expect("The webhook was not called")
+            .unwrap();
+        requests.push(request);
+    }
+
+    for request in requests.iter() {
+        assert_eq!(
+            request.headers.get(&SIGNATURE_HEADER.to_lowercase()),
+            Some(&signature(&request.body))
+        );
+        assert_eq!(
+            request
+                .headers
+                .get(&EVENT_HEADER.to_lowercase())
+                .map(String::as_str),
+            Some(JOB_FINISHED_EVENT)
+        );
+        assert_eq!(request.body, requests[0].body);
+    }
+
+    // Retries are the same delivery
+    let delivery = requests[0]
+        .headers
+        .get(&DELIVERY_HEADER.to_lowercase())
+        .unwrap();
+    assert!(requests
+        .iter()
+        .all(|r| r.headers.get(&DELIVERY_HEADER.to_lowercase()) == Some(delivery)));
+
+    let payload: WebhookPayload = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(payload.uuid, id);
+    assert_eq!(payload.status, JobStatus::Succeeded);
+    assert_eq!(payload.artifacts.len(), 1);
+    assert!(payload.artifacts[0]
+        .url
+        .ends_with(&format!("/v2/jobs/{}/artifacts/srt", id)));
+
+    // The last attempt reaches the listener before the server reads the response and records the attempt
+    let deliveries = wait_for_deliveries(&server, &format!("/v2/jobs/{}/webhooks", id), 3).await;
+    let attempts: Vec<(u32, Option<u16>, bool)> = deliveries
+        .deliveries
+        .iter()
+        .map(|d| (d.attempt, d.status_code, d.delivered))
+        .collect();
+    assert_eq!(
+        attempts,
+        [
+            (1, Some(500), false),
+            (2, Some(429), false),
+            (3, Some(200), true)
+        ]
+    );
+    assert!(deliveries
+        .deliveries
+        .iter()
+        .all(|d| d.uuid == id && d.url == url && d.delivery.to_string() == *delivery));
+}
+
+#[tokio::test]
+async fn does_not_retry_rejected_deliveries() {
+    let (url, mut received) = listen(vec![400]).await;
+    let server = TestServer::start(serde_json::json!({
+        "webhooks": { "urls": [url], "maxAttempts": 3, "initialBackoffSecs": 1 },
+    }))
+    .await;
+
+    let id = server
+        .create_job(serde_json::json!({ "path": "a.mp3" }))
+        .await;
+    server.wait_for_job(id, JobStatus::is_finished).await;
+
+    let request = tokio::time::timeout(std::time::Duration::from_secs(30), received.recv())
+        .await
+        .expect("The webhook was not called")
+        .unwrap();
+    assert!(!request
+        .headers
+        .contains_key(&SIGNATURE_HEADER.to_lowercase()));
+
+    // Longer than the backoff, so a retry would have come
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    assert!(received.try_recv().is_err());
+
+    let deliveries: GetWebhookDeliveriesResponse = server
+        .get_json(&format!("/v2/jobs/{}/webhooks", id), 200)
+        .await;
+    assert_eq!(deliveries.deliveries.len(), 1);
+    assert_eq!(deliveries.deliveries[0].status_code, Some(400));
+    assert!(!deliveries.deliveries[0].delivered);
+}
+
+#[tokio::test]
+async fn records_deliveries_to_unreachable_webhooks() {
+    let unreachable = format!("http://127.0.0.1:{}/hook", common::free_port());
+    let server = TestServer::start(serde_json::json!({
+        "webhooks": { "maxAttempts": 2, "initialBackoffSecs": 1 },
+    }))
+    .await;
+
+    let id = server
+        .create_job(serde_json::json!({ "path": "a.mp3", "callback_url": unreachable }))
+        .await;
+    server.wait_for_job(id, JobStatus::is_finished).await;
+
+    let deliveries = wait_for_deliveries(&server, &format!("/getWebhookDeliveries?uuid={}", id), 2)
+        .await
+        .deliveries;
+
+    assert_eq!(deliveries.len(), 2);
+    for (idx, delivery) in deliveries.iter().enumerate() {
+        assert_eq!(delivery.attempt, idx as u32 + 1);
+        assert_eq!(delivery.status_code, None);
+        assert!(delivery.error.is_some());
+        assert!(!delivery.delivered);
+    }
+}
+
+#[tokio::test]
+async fn has_no_deliveries_for_unknown_jobs() {
+    let server = TestServer::start(serde_json::json!({})).await;
+
+    let response = server
+        .get(&format!("/v2/jobs/{}/webhooks", uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}